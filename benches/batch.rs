@@ -0,0 +1,106 @@
+//! Compares batch lookups with one lookup per name
+//!
+//! This is a plain program rather than a `#[bench]` or criterion benchmark
+//! so that it builds with the same compiler as the crate. Run it with
+//! `cargo bench --features alloc`.
+
+use psl_types::canonical;
+use psl_types::trie::Trie;
+use psl_types::List;
+use std::time::{Duration, Instant};
+
+const SRC: &str = "\
+// ===BEGIN ICANN DOMAINS===
+com
+net
+org
+uk
+co.uk
+jp
+ac.jp
+*.kobe.jp
+!city.kobe.jp
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+blogspot.com
+github.io
+// ===END PRIVATE DOMAINS===
+";
+
+const NAMES: [&[u8]; 8] = [
+    b"www.example.com",
+    b"example.co.uk",
+    b"foo.blogspot.com",
+    b"a.b.c.kobe.jp",
+    b"www.city.kobe.jp",
+    b"user.github.io.",
+    b"printer.corp",
+    b"mail.example.org",
+];
+
+const ROUNDS: u32 = 2000;
+
+fn names() -> Vec<&'static [u8]> {
+    NAMES.iter().copied().cycle().take(1024).collect()
+}
+
+/// The same names sorted so neighbours share their suffixes
+fn sorted_names() -> Vec<&'static [u8]> {
+    let mut names = names();
+    names.sort_by(|a, b| canonical::cmp(a, b));
+    names
+}
+
+fn main() {
+    let list = Trie::parse(SRC);
+    bench_domains(&list, "domains", &names());
+    bench_domains(&list, "domains_sorted", &sorted_names());
+}
+
+fn bench_domains(list: &Trie, group: &str, names: &[&[u8]]) {
+    let mut out = vec![None; names.len()];
+    let single = time(|| {
+        for (name, domain) in names.iter().zip(out.iter_mut()) {
+            *domain = list.domain(name);
+        }
+        checksum(&out)
+    });
+    report(group, "single", single, names.len());
+    let batch = time(|| {
+        list.domains(names, &mut out);
+        checksum(&out)
+    });
+    report(group, "batch", batch, names.len());
+}
+
+/// Runs `f` `ROUNDS` times, returning the fastest run
+fn time<F: FnMut() -> usize>(mut f: F) -> Duration {
+    let mut best = Duration::from_secs(u64::max_value());
+    let mut total = 0;
+    for _ in 0..ROUNDS {
+        let start = Instant::now();
+        total += f();
+        best = best.min(start.elapsed());
+    }
+    // use the results so the lookups are not optimised away
+    assert!(total > 0);
+    best
+}
+
+fn checksum(domains: &[Option<psl_types::Domain<'_>>]) -> usize {
+    domains
+        .iter()
+        .map(|domain| domain.map_or(0, |domain| domain.as_bytes().len()))
+        .sum()
+}
+
+fn report(group: &str, name: &str, time: Duration, names: usize) {
+    println!(
+        "{}/{}: {:?} for {} names ({:?} per name)",
+        group,
+        name,
+        time,
+        names,
+        time / names as u32
+    );
+}
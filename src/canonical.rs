@@ -0,0 +1,376 @@
+//! Canonical DNS ordering of domain names
+//!
+//! [RFC 4034 section 6.1] sorts names by their labels from right to left,
+//! ignoring ASCII case, so every name sorts right after its parent and all
+//! names under a domain are next to each other:
+//!
+//! ```text
+//! example.com
+//! a.example.com
+//! z.a.example.com
+//! b.example.com
+//! example.net
+//! ```
+//!
+//! Wrap names in [`Canonical`] to use this order as a map key. A trailing
+//! `.` is ignored, like it is by the other comparisons in this crate. Keys
+//! can be looked up by any other name type as an [`AsName`] trait object,
+//! which also allows range queries for everything under a parent name:
+//!
+//! ```rust
+//! # use psl_types::{Info, List};
+//! # struct Tld;
+//! # impl List for Tld {
+//! #     fn find<'a, T: Iterator<Item = &'a [u8]>>(&self, mut labels: T) -> Info {
+//! #         Info { len: labels.next().map_or(0, <[u8]>::len), typ: None }
+//! #     }
+//! # }
+//! # let list = Tld;
+//! use psl_types::canonical::{is_subdomain, AsName, Canonical};
+//! use std::collections::BTreeSet;
+//! use std::ops::Bound::{Included, Unbounded};
+//!
+//! let hosts = vec![
+//!     String::from("www.example.com"),
+//!     String::from("example.net"),
+//!     String::from("a.other.com"),
+//! ];
+//! let domains: BTreeSet<_> = hosts
+//!     .iter()
+//!     .filter_map(|host| list.domain(host.as_bytes()))
+//!     .map(Canonical)
+//!     .collect();
+//!
+//! // every registrable domain under `com`
+//! let parent: &[u8] = b"com";
+//! let under: Vec<_> = domains
+//!     .range::<dyn AsName, _>((Included(&parent as &dyn AsName), Unbounded))
+//!     .take_while(|domain| is_subdomain(domain.0.as_bytes(), parent))
+//!     .map(|domain| domain.0)
+//!     .collect();
+//! assert_eq!(under, ["example.com", "other.com"]);
+//! ```
+//!
+//! [RFC 4034 section 6.1]: https://www.rfc-editor.org/rfc/rfc4034#section-6.1
+
+use crate::{is_dot, strip_dot, Domain, DomainStr, Suffix, SuffixStr};
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+/// A domain name that can be put in canonical order
+pub trait AsName {
+    /// The domain name as bytes
+    fn as_name(&self) -> &[u8];
+}
+
+impl AsName for [u8] {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self
+    }
+}
+
+impl AsName for str {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl PartialEq for dyn AsName + '_ {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        strip_dot(self.as_name()).eq_ignore_ascii_case(strip_dot(other.as_name()))
+    }
+}
+
+impl Eq for dyn AsName + '_ {}
+
+impl Ord for dyn AsName + '_ {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp(self.as_name(), other.as_name())
+    }
+}
+
+impl PartialOrd for dyn AsName + '_ {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for dyn AsName + '_ {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash(self.as_name(), state);
+    }
+}
+
+impl<T: AsName + ?Sized> AsName for &T {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        (**self).as_name()
+    }
+}
+
+impl AsName for Suffix<'_> {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsName for Domain<'_> {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsName for SuffixStr<'_> {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+impl AsName for DomainStr<'_> {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsName for crate::owned::SuffixBuf {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl AsName for crate::owned::DomainBuf {
+    #[inline]
+    fn as_name(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+/// Compares and hashes a domain name in canonical DNS order
+#[derive(Copy, Clone, Default, Debug)]
+pub struct Canonical<T>(pub T);
+
+impl<T: AsName> Canonical<T> {
+    /// Compares with a name of another type in canonical DNS order
+    #[inline]
+    #[must_use]
+    pub fn cmp_name<U: AsName>(&self, other: &Canonical<U>) -> Ordering {
+        cmp(self.0.as_name(), other.0.as_name())
+    }
+}
+
+impl<T: AsName> PartialEq for Canonical<T> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        (&self.0 as &dyn AsName) == (&other.0 as &dyn AsName)
+    }
+}
+
+impl<T: AsName> Eq for Canonical<T> {}
+
+impl<T: AsName> Ord for Canonical<T> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        cmp(self.0.as_name(), other.0.as_name())
+    }
+}
+
+impl<T: AsName> PartialOrd for Canonical<T> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T: AsName> Hash for Canonical<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        hash(self.0.as_name(), state);
+    }
+}
+
+impl<'a, T: AsName + 'a> Borrow<dyn AsName + 'a> for Canonical<T> {
+    #[inline]
+    fn borrow(&self) -> &(dyn AsName + 'a) {
+        &self.0
+    }
+}
+
+/// Compares two domain names in canonical DNS order
+#[must_use]
+pub fn cmp(a: &[u8], b: &[u8]) -> Ordering {
+    let mut a = strip_dot(a).rsplit(is_dot);
+    let mut b = strip_dot(b).rsplit(is_dot);
+    loop {
+        let ordering = match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(a), Some(b)) => lowercase(a).cmp(lowercase(b)),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+}
+
+/// Whether `name` is `parent` or one of its subdomains, ignoring ASCII case
+#[must_use]
+pub fn is_subdomain(name: &[u8], parent: &[u8]) -> bool {
+    let name = strip_dot(name);
+    let parent = strip_dot(parent);
+    if parent.is_empty() {
+        return true;
+    }
+    if name.len() < parent.len() {
+        return false;
+    }
+    let start = name.len() - parent.len();
+    name[start..].eq_ignore_ascii_case(parent) && (start == 0 || name[start - 1] == b'.')
+}
+
+fn hash<H: Hasher>(name: &[u8], state: &mut H) {
+    let name = strip_dot(name);
+    for byte in name {
+        state.write_u8(byte.to_ascii_lowercase());
+    }
+    state.write_usize(name.len());
+}
+
+fn lowercase(label: &[u8]) -> impl Iterator<Item = u8> + '_ {
+    label.iter().map(u8::to_ascii_lowercase)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cmp, is_subdomain, Canonical};
+    use crate::fixture::Tld;
+    use crate::List;
+    use core::cmp::Ordering;
+
+    #[test]
+    fn rfc_4034_example() {
+        // The example from RFC 4034 section 6.1 without the escaped labels
+        let sorted: [&[u8]; 6] = [
+            b"example",
+            b"a.example",
+            b"yljkjljk.a.example",
+            b"Z.a.example",
+            b"zABC.a.EXAMPLE",
+            b"z.example",
+        ];
+        for (index, a) in sorted.iter().enumerate() {
+            for (other, b) in sorted.iter().enumerate() {
+                assert_eq!(cmp(a, b), index.cmp(&other), "{:?} {:?}", a, b);
+            }
+        }
+        assert!(sorted
+            .windows(2)
+            .all(|pair| Canonical(pair[0]) < Canonical(pair[1])));
+    }
+
+    #[test]
+    fn case_and_trailing_dot() {
+        assert_eq!(
+            cmp(b"WWW.Example.COM.", b"www.example.com"),
+            Ordering::Equal
+        );
+        assert_eq!(
+            Canonical(&b"Example.COM"[..]),
+            Canonical(&b"example.com."[..])
+        );
+        assert_eq!(cmp(b"", b"com"), Ordering::Less);
+        assert_eq!(cmp(b".", b""), Ordering::Equal);
+    }
+
+    #[test]
+    fn domains_and_suffixes() {
+        let a = Tld.domain(b"www.b.example").expect("domain name");
+        let b = Tld.domain(b"a.other.").expect("domain name");
+        assert!(a > b);
+        assert!(Canonical(a) < Canonical(b));
+
+        let suffix = Tld.suffix(b"a.example").expect("suffix");
+        assert_eq!(Canonical(suffix).cmp_name(&Canonical(a)), Ordering::Less);
+        assert_eq!(
+            Canonical("EXAMPLE").cmp_name(&Canonical(suffix)),
+            Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn subdomains() {
+        assert!(is_subdomain(b"a.example.com", b"example.com"));
+        assert!(is_subdomain(b"Example.com.", b"example.COM"));
+        assert!(is_subdomain(b"example.com", b"."));
+        assert!(!is_subdomain(b"anexample.com", b"example.com"));
+        assert!(!is_subdomain(b"com", b"example.com"));
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn btree_range() {
+        extern crate alloc;
+        use alloc::collections::BTreeSet;
+        use alloc::vec::Vec;
+
+        let names: [&[u8]; 6] = [
+            b"example.net",
+            b"b.example.com",
+            b"example.com",
+            b"z.a.example.com",
+            b"com",
+            b"a.example.com",
+        ];
+        let set: BTreeSet<_> = names.iter().map(|name| Canonical(*name)).collect();
+        let parent = &b"example.com"[..];
+        let under: Vec<_> = set
+            .range(Canonical(parent)..)
+            .take_while(|name| is_subdomain(name.0, parent))
+            .map(|name| name.0)
+            .collect();
+        let expected: [&[u8]; 4] = [
+            b"example.com",
+            b"a.example.com",
+            b"z.a.example.com",
+            b"b.example.com",
+        ];
+        assert_eq!(under, expected);
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn domain_keys() {
+        extern crate alloc;
+        use super::AsName;
+        use alloc::collections::BTreeSet;
+        use alloc::vec::Vec;
+        use core::ops::Bound::{Included, Unbounded};
+
+        let names: [&[u8]; 4] = [b"www.example.com", b"example.net", b"a.b.com.", b"com"];
+        let set: BTreeSet<_> = names
+            .iter()
+            .filter_map(|name| Tld.domain(name))
+            .map(Canonical)
+            .collect();
+        let parent = &b"com"[..];
+        let under: Vec<_> = set
+            .range::<dyn AsName, _>((Included(&parent as &dyn AsName), Unbounded))
+            .take_while(|domain| is_subdomain(domain.0.as_bytes(), parent))
+            .map(|domain| domain.0)
+            .collect();
+        assert_eq!(under, ["b.com.", "example.com"]);
+        assert!(set.contains(&&b"EXAMPLE.net."[..] as &dyn AsName));
+    }
+}
@@ -0,0 +1,126 @@
+//! Case-insensitive lookups for lists that only support lowercase input
+
+use crate::{Info, List, Match, Metadata};
+use core::mem;
+
+/// The longest domain name that can be lowercased, in bytes
+const MAX_LEN: usize = 255;
+
+/// Lowercases ASCII labels before passing them to the inner list
+///
+/// Labels that are already lowercase are passed through as they are and
+/// the rest are copied into a buffer on the stack so no allocation is
+/// needed. Since lowercasing does not change the length of a label, the
+/// returned suffixes and domains point into the original mixed-case input.
+///
+/// Labels that don't fit into the buffer (i.e. in names longer than 255
+/// bytes, which are not valid domain names) are passed through unchanged.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct CaseInsensitive<L>(pub L);
+
+impl<L: List> List for CaseInsensitive<L> {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let mut buf = [0; MAX_LEN];
+        self.0.find(Lowercase::new(labels, &mut buf))
+    }
+
+    #[inline]
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let mut buf = [0; MAX_LEN];
+        self.0.find_match(Lowercase::new(labels, &mut buf))
+    }
+
+    #[inline]
+    fn find_metadata<'a, T>(&self, labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let mut buf = [0; MAX_LEN];
+        self.0.find_metadata(Lowercase::new(labels, &mut buf))
+    }
+}
+
+/// An iterator that lowercases labels that contain uppercase ASCII letters
+struct Lowercase<'b, T> {
+    labels: T,
+    buf: &'b mut [u8],
+}
+
+impl<'b, T> Lowercase<'b, T> {
+    fn new(labels: T, buf: &'b mut [u8]) -> Self {
+        Lowercase { labels, buf }
+    }
+}
+
+impl<'a: 'b, 'b, T> Iterator for Lowercase<'b, T>
+where
+    T: Iterator<Item = &'a [u8]>,
+{
+    type Item = &'b [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let label = self.labels.next()?;
+        if !label.iter().any(u8::is_ascii_uppercase) || label.len() > self.buf.len() {
+            return Some(label);
+        }
+        let (lowercase, rest) = mem::take(&mut self.buf).split_at_mut(label.len());
+        self.buf = rest;
+        lowercase.copy_from_slice(label);
+        lowercase.make_ascii_lowercase();
+        Some(lowercase)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::CaseInsensitive;
+    use crate::{Info, List, Type};
+
+    /// Only knows about lowercase `com`
+    struct Com;
+
+    impl List for Com {
+        fn find<'a, T>(&self, mut labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            match labels.next() {
+                Some(b"com") => Info {
+                    len: 3,
+                    typ: Some(Type::Icann),
+                },
+                Some(label) => Info {
+                    len: label.len(),
+                    typ: None,
+                },
+                None => Info { len: 0, typ: None },
+            }
+        }
+    }
+
+    #[test]
+    fn mixed_case() {
+        assert!(!Com.suffix(b"WWW.Example.COM").expect("suffix").is_known());
+
+        let list = CaseInsensitive(Com);
+        let domain = list.domain(b"WWW.Example.COM.").expect("domain name");
+        assert_eq!(domain.as_bytes(), b"Example.COM.");
+        assert_eq!(domain.suffix().as_bytes(), b"COM.");
+        assert_eq!(domain.suffix().typ(), Some(Type::Icann));
+    }
+
+    #[test]
+    fn lowercase() {
+        let list = CaseInsensitive(Com);
+        let suffix = list.suffix(b"www.example.com").expect("suffix");
+        assert_eq!(suffix, "com");
+        assert!(suffix.is_known());
+    }
+}
@@ -0,0 +1,515 @@
+//! A compact binary format for compiled lists
+//!
+//! Lists in this format can be shipped as data files and looked up in
+//! place, for example from `include_bytes!` or a memory mapped file, so
+//! updating the list does not require a rebuild. [`encode`] turns a
+//! [`Trie`] into this format and [`Compiled`] reads it without copying.
+//!
+//! All integers are little endian. The format starts with a 20 byte header:
+//!
+//! | bytes  | contents                                          |
+//! |--------|---------------------------------------------------|
+//! | 0..4   | the magic bytes `PSLT`                            |
+//! | 4..6   | the format version, currently 1                   |
+//! | 6..8   | reserved, must be 0                               |
+//! | 8..12  | the number of nodes                               |
+//! | 12..16 | the length of the label data                      |
+//! | 16..20 | the CRC-32 of everything after the header         |
+//!
+//! It is followed by a table of 16 byte nodes in breadth first order,
+//! starting with the root, and then the label data. Each node has the
+//! offset (4 bytes) and length (2 bytes) of its label in the label data,
+//! a flags byte, a reserved byte, and the index of its first child (4
+//! bytes) and its number of children (4 bytes). The children of a node
+//! are consecutive and sorted by label.
+//!
+//! [`Trie`]: ../trie/struct.Trie.html
+
+use crate::{walk, Info, Kind, List, Match, Step, Type};
+use core::convert::TryInto;
+use core::fmt;
+
+const MAGIC: &[u8; 4] = b"PSLT";
+const VERSION: u16 = 1;
+const HEADER_LEN: usize = 20;
+const NODE_LEN: usize = 16;
+
+const RULE: u8 = 1;
+const EXCEPTION: u8 = 1 << 1;
+const RULE_PRIVATE: u8 = 1 << 2;
+const WILDCARD: u8 = 1 << 3;
+const WILDCARD_PRIVATE: u8 = 1 << 4;
+
+/// An error found while loading a compiled list
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub enum Error {
+    /// The data is shorter than its header says it is
+    Truncated,
+    /// The data does not start with the magic bytes
+    BadMagic,
+    /// The data is in a version of the format this crate does not support
+    UnsupportedVersion(u16),
+    /// The checksum does not match the data
+    ChecksumMismatch,
+    /// The reserved header bytes are not 0, or a node points outside of
+    /// the node table or the label data
+    Corrupt,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Truncated => f.write_str("compiled list is truncated"),
+            Error::BadMagic => f.write_str("not a compiled list"),
+            Error::UnsupportedVersion(version) => {
+                write!(f, "unsupported compiled list version {}", version)
+            }
+            Error::ChecksumMismatch => f.write_str("compiled list checksum mismatch"),
+            Error::Corrupt => f.write_str("compiled list is corrupt"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// A list read in place from the compact binary format
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Compiled<'a> {
+    nodes: &'a [u8],
+    labels: &'a [u8],
+}
+
+impl<'a> Compiled<'a> {
+    /// Checks the header, checksum and node table of `bytes`
+    ///
+    /// This is the only time the whole of `bytes` is read. Lookups only
+    /// read the nodes they visit.
+    pub fn new(bytes: &'a [u8]) -> Result<Self, Error> {
+        if bytes.len() < HEADER_LEN {
+            return Err(Error::Truncated);
+        }
+        if &bytes[..4] != MAGIC {
+            return Err(Error::BadMagic);
+        }
+        let version = u16::from_le_bytes([bytes[4], bytes[5]]);
+        if version != VERSION {
+            return Err(Error::UnsupportedVersion(version));
+        }
+        if bytes[6..8] != [0, 0] {
+            return Err(Error::Corrupt);
+        }
+        let node_count = read_u32(bytes, 8);
+        let labels_len = read_u32(bytes, 12);
+        let checksum = read_u32(bytes, 16);
+        let nodes_len = node_count.checked_mul(NODE_LEN).ok_or(Error::Corrupt)?;
+        let len = HEADER_LEN
+            .checked_add(nodes_len)
+            .and_then(|len| len.checked_add(labels_len))
+            .ok_or(Error::Corrupt)?;
+        if bytes.len() < len {
+            return Err(Error::Truncated);
+        }
+        let body = &bytes[HEADER_LEN..len];
+        if crc32(body) as usize != checksum {
+            return Err(Error::ChecksumMismatch);
+        }
+        let (nodes, labels) = body.split_at(nodes_len);
+        if node_count == 0 {
+            return Err(Error::Corrupt);
+        }
+        let list = Compiled { nodes, labels };
+        for index in 0..node_count {
+            let node = list.record(index);
+            let label_end = node.label_offset.checked_add(node.label_len);
+            let children_end = node.children_start.checked_add(node.children_len);
+            if label_end.filter(|&end| end <= labels.len()).is_none()
+                || children_end.filter(|&end| end <= node_count).is_none()
+            {
+                return Err(Error::Corrupt);
+            }
+        }
+        Ok(list)
+    }
+
+    fn record(&self, index: usize) -> Record {
+        let offset = index * NODE_LEN;
+        let node = &self.nodes[offset..offset + NODE_LEN];
+        Record {
+            label_offset: read_u32(node, 0),
+            label_len: usize::from(u16::from_le_bytes([node[4], node[5]])),
+            flags: node[6],
+            children_start: read_u32(node, 8),
+            children_len: read_u32(node, 12),
+        }
+    }
+
+    fn node(&self, index: usize) -> Node<'a> {
+        Node {
+            list: *self,
+            index,
+            record: self.record(index),
+        }
+    }
+}
+
+impl List for Compiled<'_> {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.find_match(labels).info
+    }
+
+    #[inline]
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        walk::find_match(self.node(0), labels)
+    }
+
+    #[inline]
+    fn find_all<'a, T>(&self, labels: T, out: &mut [Info]) -> usize
+    where
+        T: Iterator<Item = &'a [u8]> + Clone,
+    {
+        walk::find_all(self.node(0), labels, out)
+    }
+
+    /// Reuses the walk of the previous name for the labels they share
+    #[inline]
+    fn find_batch(&self, names: &[&[u8]], out: &mut [Info]) {
+        walk::find_batch(self.node(0), names, out);
+    }
+
+    /// Continues from the node reached by the previous labels
+    fn find_step(&self, labels: &[&[u8]], prev: Option<Step>) -> Step {
+        let label = match labels.last() {
+            Some(label) => label,
+            None => return Step::default(),
+        };
+        let parent = match prev {
+            Some(prev) => prev
+                .node
+                .filter(|&index| index < self.nodes.len() / NODE_LEN)
+                .map(|index| self.node(index)),
+            None => Some(self.node(0)),
+        };
+        let len = labels.iter().map(|label| label.len()).sum::<usize>() + labels.len() - 1;
+        let (node, found) = walk::step(parent, label, len, prev.map(|prev| prev.found));
+        Step {
+            found,
+            node: node.map(|node| node.index),
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Record {
+    label_offset: usize,
+    label_len: usize,
+    flags: u8,
+    children_start: usize,
+    children_len: usize,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Node<'a> {
+    list: Compiled<'a>,
+    index: usize,
+    record: Record,
+}
+
+impl<'a> Node<'a> {
+    fn label(&self) -> &'a [u8] {
+        let start = self.record.label_offset;
+        &self.list.labels[start..start + self.record.label_len]
+    }
+}
+
+impl walk::Node for Node<'_> {
+    fn child(self, label: &[u8]) -> Option<Self> {
+        let mut low = self.record.children_start;
+        let mut high = low + self.record.children_len;
+        while low < high {
+            let mid = low + (high - low) / 2;
+            let child = self.list.node(mid);
+            match child.label().cmp(label) {
+                core::cmp::Ordering::Less => low = mid + 1,
+                core::cmp::Ordering::Greater => high = mid,
+                core::cmp::Ordering::Equal => return Some(child),
+            }
+        }
+        None
+    }
+
+    fn rule(self) -> Option<(Kind, Type)> {
+        let flags = self.record.flags;
+        if flags & RULE == 0 {
+            return None;
+        }
+        let kind = if flags & EXCEPTION == 0 {
+            Kind::Normal
+        } else {
+            Kind::Exception
+        };
+        Some((kind, typ(flags & RULE_PRIVATE)))
+    }
+
+    fn wildcard(self) -> Option<Type> {
+        let flags = self.record.flags;
+        if flags & WILDCARD == 0 {
+            return None;
+        }
+        Some(typ(flags & WILDCARD_PRIVATE))
+    }
+}
+
+fn typ(private: u8) -> Type {
+    if private == 0 {
+        Type::Icann
+    } else {
+        Type::Private
+    }
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> usize {
+    let bytes = bytes[offset..offset + 4].try_into().unwrap_or([0; 4]);
+    u32::from_le_bytes(bytes) as usize
+}
+
+/// CRC-32 as used by zlib and PNG
+fn crc32(bytes: &[u8]) -> u32 {
+    let mut crc = !0_u32;
+    for byte in bytes {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+#[cfg(feature = "alloc")]
+mod encode {
+    use super::{
+        crc32, EXCEPTION, HEADER_LEN, MAGIC, NODE_LEN, RULE, RULE_PRIVATE, VERSION, WILDCARD,
+        WILDCARD_PRIVATE,
+    };
+    use crate::trie::{Node, Trie};
+    use crate::{Kind, Type};
+    use alloc::vec;
+    use alloc::vec::Vec;
+    use core::convert::TryFrom;
+
+    /// Encodes a list into the compact binary format
+    ///
+    /// # Panics
+    ///
+    /// Panics if the list has more than `u32::MAX` nodes or labels longer
+    /// than `u16::MAX` bytes.
+    #[must_use]
+    pub fn encode(trie: &Trie) -> Vec<u8> {
+        let empty: &[u8] = &[];
+        let mut order = vec![(empty, &trie.root)];
+        let mut children_start = Vec::new();
+        let mut index = 0;
+        while let Some(&(_, node)) = order.get(index) {
+            children_start.push(order.len());
+            order.extend(node.children.iter().map(|(label, child)| (&**label, child)));
+            index += 1;
+        }
+
+        let mut nodes = Vec::with_capacity(order.len() * NODE_LEN);
+        let mut labels = Vec::new();
+        for ((label, node), children_start) in order.iter().zip(children_start) {
+            nodes.extend_from_slice(&u32(labels.len()).to_le_bytes());
+            let label_len = u16::try_from(label.len()).expect("label is too long");
+            nodes.extend_from_slice(&label_len.to_le_bytes());
+            nodes.push(flags(node));
+            nodes.push(0);
+            nodes.extend_from_slice(&u32(children_start).to_le_bytes());
+            nodes.extend_from_slice(&u32(node.children.len()).to_le_bytes());
+            labels.extend_from_slice(label);
+        }
+
+        let mut bytes = Vec::with_capacity(HEADER_LEN + nodes.len() + labels.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&[0, 0]);
+        bytes.extend_from_slice(&u32(order.len()).to_le_bytes());
+        bytes.extend_from_slice(&u32(labels.len()).to_le_bytes());
+        bytes.extend_from_slice(&[0; 4]);
+        bytes.extend_from_slice(&nodes);
+        bytes.extend_from_slice(&labels);
+        let checksum = crc32(&bytes[HEADER_LEN..]);
+        bytes[16..20].copy_from_slice(&checksum.to_le_bytes());
+        bytes
+    }
+
+    fn flags(node: &Node) -> u8 {
+        let mut flags = 0;
+        if let Some((kind, typ)) = node.rule {
+            flags |= RULE;
+            if kind == Kind::Exception {
+                flags |= EXCEPTION;
+            }
+            if typ == Type::Private {
+                flags |= RULE_PRIVATE;
+            }
+        }
+        if let Some(typ) = node.wildcard {
+            flags |= WILDCARD;
+            if typ == Type::Private {
+                flags |= WILDCARD_PRIVATE;
+            }
+        }
+        flags
+    }
+
+    fn u32(len: usize) -> u32 {
+        u32::try_from(len).expect("list is too large")
+    }
+}
+
+#[cfg(feature = "alloc")]
+pub use encode::encode;
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::{encode, Compiled, Error};
+    use crate::trie::Trie;
+    use crate::{Kind, List, Type};
+
+    const SRC: &str = "\
+// ===BEGIN ICANN DOMAINS===
+com
+uk
+co.uk
+*.kobe.jp
+!city.kobe.jp
+公司.cn
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+blogspot.com
+*.compute.amazonaws.com
+// ===END PRIVATE DOMAINS===
+";
+
+    #[test]
+    fn round_trip() {
+        let trie = Trie::parse(SRC);
+        let bytes = encode(&trie);
+        let list = Compiled::new(&bytes).expect("compiled list");
+        for name in [
+            "www.example.com",
+            "example.co.uk",
+            "foo.blogspot.com",
+            "a.b.c.kobe.jp",
+            "www.city.kobe.jp",
+            "a.b.eu-west-1.compute.amazonaws.com",
+            "食狮.公司.cn",
+            "printer.corp",
+            "",
+        ]
+        .iter()
+        {
+            let name = name.as_bytes();
+            assert_eq!(list.domain(name), trie.domain(name));
+            assert_eq!(list.rule(name), trie.rule(name));
+            assert!(list.all_suffixes(name).eq(trie.all_suffixes(name)));
+        }
+        let rule = list
+            .rule(b"a.b.eu-west-1.compute.amazonaws.com")
+            .expect("rule");
+        assert_eq!(rule.kind(), Kind::Wildcard);
+        assert_eq!(rule.typ(), Some(Type::Private));
+    }
+
+    #[test]
+    fn find_batch() {
+        let trie = Trie::parse(SRC);
+        let bytes = encode(&trie);
+        let list = Compiled::new(&bytes).expect("compiled list");
+        crate::trie::test::check_batch(&list);
+    }
+
+    #[test]
+    fn cursor() {
+        let trie = Trie::parse(SRC);
+        let bytes = encode(&trie);
+        let list = Compiled::new(&bytes).expect("compiled list");
+        let names: [&[&[u8]]; 6] = [
+            &[b"jp", b"kobe", b"city", b"www"],
+            &[b"jp", b"kobe", b"c", b"b"],
+            &[b"com", b"amazonaws", b"compute", b"eu-west-1", b"a"],
+            &[b"com", b"blogspot", b"foo"],
+            &[b"com", b"example", b"www"],
+            &[b"corp", b"printer"],
+        ];
+        for labels in names.iter() {
+            let mut compiled = list.cursor();
+            let mut default = trie.cursor();
+            for (index, label) in labels.iter().enumerate() {
+                compiled.push(label);
+                default.push(label);
+                let expected = trie.find_match(labels[..=index].iter().copied());
+                assert_eq!(compiled.current(), expected);
+                assert_eq!(default.current(), expected);
+            }
+        }
+
+        let mut cursor = list.cursor();
+        cursor.push(b"jp");
+        cursor.push(b"kobe");
+        cursor.push(b"city");
+        assert_eq!(cursor.current().kind, Kind::Exception);
+        cursor.pop();
+        cursor.push(b"c");
+        assert_eq!(cursor.current().kind, Kind::Wildcard);
+        assert_eq!(cursor.info().len, 9);
+    }
+
+    #[test]
+    fn empty_list() {
+        let bytes = encode(&Trie::new());
+        let list = Compiled::new(&bytes).expect("compiled list");
+        assert_eq!(list.suffix(b"example.com").expect("suffix"), "com");
+    }
+
+    #[test]
+    fn invalid_data() {
+        let mut bytes = encode(&Trie::parse(SRC));
+        assert_eq!(Compiled::new(&bytes[..10]), Err(Error::Truncated));
+
+        bytes[7] = 1;
+        assert_eq!(Compiled::new(&bytes), Err(Error::Corrupt));
+        bytes[7] = 0;
+        assert!(Compiled::new(&bytes).is_ok());
+        assert_eq!(
+            Compiled::new(&bytes[..bytes.len() - 1]),
+            Err(Error::Truncated)
+        );
+
+        let last = bytes.len() - 1;
+        bytes[last] ^= 1;
+        assert_eq!(Compiled::new(&bytes), Err(Error::ChecksumMismatch));
+
+        bytes[4] = 2;
+        assert_eq!(Compiled::new(&bytes), Err(Error::UnsupportedVersion(2)));
+
+        bytes[0] = b'X';
+        assert_eq!(Compiled::new(&bytes), Err(Error::BadMagic));
+    }
+
+    #[test]
+    #[cfg(feature = "conformance")]
+    fn conformance() {
+        let bytes = encode(&Trie::parse(crate::trie::test::SRC));
+        let report = crate::conformance::run(&Compiled::new(&bytes).expect("compiled list"));
+        assert!(report.is_ok(), "{}", report);
+    }
+}
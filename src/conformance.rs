@@ -0,0 +1,370 @@
+//! Conformance tests for `List` implementations
+//!
+//! This module embeds the `checkPublicSuffix` test vectors from the
+//! upstream [`test_psl.txt`] file so that every implementation of
+//! [`List`] can run the same suite from its own tests.
+//!
+//! ```rust
+//! # use psl_types::trie::Trie;
+//! # let my_list = Trie::parse("
+//! # // ===BEGIN ICANN DOMAINS===
+//! # ac
+//! # biz
+//! # com
+//! # jp
+//! # ac.jp
+//! # kyoto.jp
+//! # ide.kyoto.jp
+//! # *.kobe.jp
+//! # !city.kobe.jp
+//! # *.ck
+//! # !www.ck
+//! # *.mm
+//! # us
+//! # ak.us
+//! # k12.ak.us
+//! # cn
+//! # com.cn
+//! # 公司.cn
+//! # xn--55qx5d.cn
+//! # 中国
+//! # xn--fiqs8s
+//! # // ===END ICANN DOMAINS===
+//! # // ===BEGIN PRIVATE DOMAINS===
+//! # uk.com
+//! # blogspot.com
+//! # // ===END PRIVATE DOMAINS===
+//! # ");
+//! let report = psl_types::conformance::run(&my_list);
+//! assert!(report.is_ok(), "{}", report);
+//! ```
+//!
+//! The upstream test vectors expect lowercase output so the registrable
+//! domain returned by the list is compared case-insensitively.
+//!
+//! [`test_psl.txt`]: https://github.com/publicsuffix/list/blob/master/tests/test_psl.txt
+
+use crate::{Domain, List};
+use alloc::vec::Vec;
+use core::fmt;
+use core::iter::Enumerate;
+use core::str::Lines;
+
+/// The upstream `test_psl.txt` test vectors
+pub const TEST_PSL: &str = include_str!("conformance/test_psl.txt");
+
+/// A single `checkPublicSuffix` test case
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Case<'a> {
+    /// The line the case is on, starting at 1
+    pub line: usize,
+    /// The domain name to look up, `None` for a `null` input
+    pub input: Option<&'a str>,
+    /// The expected registrable domain, `None` if there is none
+    pub expected: Option<&'a str>,
+}
+
+impl<'a> Case<'a> {
+    /// Runs this case against `list`
+    ///
+    /// Returns `None` if the case has a `null` input, since such
+    /// input cannot be passed to a `List`. The upstream vectors treat
+    /// names with a leading `.` as invalid, so those get no domain here
+    /// whatever the list returns.
+    pub fn check<L: List>(&self, list: &L) -> Option<Outcome<'a>> {
+        let input = self.input?;
+        let actual = if input.starts_with('.') {
+            None
+        } else {
+            list.domain(input.as_bytes())
+        };
+        let passed = match (actual, self.expected) {
+            (Some(actual), Some(expected)) => {
+                actual.as_bytes().eq_ignore_ascii_case(expected.as_bytes())
+            }
+            (None, None) => true,
+            _ => false,
+        };
+        Some(Outcome {
+            case: *self,
+            actual,
+            passed,
+        })
+    }
+}
+
+impl fmt::Display for Case<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("checkPublicSuffix(")?;
+        write_arg(f, self.input)?;
+        f.write_str(", ")?;
+        write_arg(f, self.expected)?;
+        f.write_str(");")
+    }
+}
+
+fn write_arg<T: fmt::Display>(f: &mut fmt::Formatter<'_>, arg: Option<T>) -> fmt::Result {
+    match arg {
+        Some(arg) => write!(f, "'{}'", arg),
+        None => f.write_str("null"),
+    }
+}
+
+/// A line that is neither a comment nor a valid test case
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Error {
+    /// The line the error is on, starting at 1
+    pub line: usize,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: invalid test case", self.line)
+    }
+}
+
+/// An iterator over the test cases in a `test_psl.txt` file
+#[derive(Clone, Debug)]
+pub struct Cases<'a> {
+    lines: Enumerate<Lines<'a>>,
+}
+
+impl<'a> Iterator for Cases<'a> {
+    type Item = Result<Case<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, line) in &mut self.lines {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with("//") {
+                continue;
+            }
+            let line_no = index + 1;
+            return Some(parse_case(line, line_no).ok_or(Error { line: line_no }));
+        }
+        None
+    }
+}
+
+/// Parses the test cases in `src`, which must be in the `test_psl.txt` format
+#[must_use]
+pub fn cases(src: &str) -> Cases<'_> {
+    Cases {
+        lines: src.lines().enumerate(),
+    }
+}
+
+fn parse_case(line: &str, line_no: usize) -> Option<Case<'_>> {
+    const PREFIX: &str = "checkPublicSuffix(";
+    const SUFFIX: &str = ");";
+    if !line.starts_with(PREFIX)
+        || !line.ends_with(SUFFIX)
+        || line.len() < PREFIX.len() + SUFFIX.len()
+    {
+        return None;
+    }
+    let args = &line[PREFIX.len()..line.len() - SUFFIX.len()];
+    let (input, rest) = parse_arg(args)?;
+    let rest = rest.trim_start();
+    if !rest.starts_with(',') {
+        return None;
+    }
+    let (expected, rest) = parse_arg(&rest[1..])?;
+    if !rest.trim().is_empty() {
+        return None;
+    }
+    Some(Case {
+        line: line_no,
+        input,
+        expected,
+    })
+}
+
+/// Parses a `null` or single quoted argument, returning the rest of the input
+fn parse_arg(args: &str) -> Option<(Option<&str>, &str)> {
+    let args = args.trim_start();
+    if args.starts_with("null") {
+        return Some((None, &args[4..]));
+    }
+    if !args.starts_with('\'') {
+        return None;
+    }
+    let args = &args[1..];
+    let end = args.find('\'')?;
+    Some((Some(&args[..end]), &args[end + 1..]))
+}
+
+/// The result of running a single test case
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Outcome<'a> {
+    /// The test case that was run
+    pub case: Case<'a>,
+    /// The registrable domain the list returned
+    pub actual: Option<Domain<'a>>,
+    /// Whether the list returned the expected domain
+    pub passed: bool,
+}
+
+impl fmt::Display for Outcome<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {} ", self.case.line, self.case)?;
+        if self.passed {
+            f.write_str("passed")
+        } else {
+            f.write_str("returned ")?;
+            write_arg(f, self.actual)
+        }
+    }
+}
+
+/// The results of running a set of test cases against a list
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Report<'a> {
+    outcomes: Vec<Outcome<'a>>,
+    skipped: Vec<Case<'a>>,
+    errors: Vec<Error>,
+}
+
+impl<'a> Report<'a> {
+    /// Whether every test case passed and every line could be parsed
+    #[must_use]
+    pub fn is_ok(&self) -> bool {
+        self.errors.is_empty() && self.outcomes.iter().all(|outcome| outcome.passed)
+    }
+
+    /// All the test cases that were run
+    #[must_use]
+    pub fn outcomes(&self) -> &[Outcome<'a>] {
+        &self.outcomes
+    }
+
+    /// The test cases that passed
+    pub fn passed(&self) -> impl Iterator<Item = &Outcome<'a>> {
+        self.outcomes.iter().filter(|outcome| outcome.passed)
+    }
+
+    /// The test cases that failed
+    pub fn failed(&self) -> impl Iterator<Item = &Outcome<'a>> {
+        self.outcomes.iter().filter(|outcome| !outcome.passed)
+    }
+
+    /// The test cases that were not run because they have a `null` input
+    #[must_use]
+    pub fn skipped(&self) -> &[Case<'a>] {
+        &self.skipped
+    }
+
+    /// The lines that could not be parsed
+    #[must_use]
+    pub fn errors(&self) -> &[Error] {
+        &self.errors
+    }
+}
+
+impl fmt::Display for Report<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} passed, {} failed, {} skipped, {} invalid",
+            self.passed().count(),
+            self.failed().count(),
+            self.skipped.len(),
+            self.errors.len(),
+        )?;
+        for outcome in self.failed() {
+            write!(f, "\n{}", outcome)?;
+        }
+        for error in &self.errors {
+            write!(f, "\n{}", error)?;
+        }
+        Ok(())
+    }
+}
+
+/// Runs the upstream test vectors against `list`
+pub fn run<L: List>(list: &L) -> Report<'static> {
+    run_with(list, TEST_PSL)
+}
+
+/// Runs the test cases in `src` against `list`
+///
+/// `src` must be in the `test_psl.txt` format. This is useful for
+/// running a newer copy of the upstream file or custom test cases.
+pub fn run_with<'a, L: List>(list: &L, src: &'a str) -> Report<'a> {
+    let mut report = Report {
+        outcomes: Vec::new(),
+        skipped: Vec::new(),
+        errors: Vec::new(),
+    };
+    for case in cases(src) {
+        match case {
+            Ok(case) => match case.check(list) {
+                Some(outcome) => report.outcomes.push(outcome),
+                None => report.skipped.push(case),
+            },
+            Err(error) => report.errors.push(error),
+        }
+    }
+    report
+}
+
+#[cfg(test)]
+mod test {
+    use super::{cases, run, run_with, Case, Error, TEST_PSL};
+    use crate::fixture::Tld;
+    use alloc::string::ToString;
+
+    #[test]
+    fn upstream_cases_parse() {
+        let mut count = 0;
+        for case in cases(TEST_PSL) {
+            case.expect("valid test case");
+            count += 1;
+        }
+        assert_eq!(count, 78);
+    }
+
+    #[test]
+    fn parse_case() {
+        let mut cases = cases("// comment\n\ncheckPublicSuffix('WwW.example.COM', 'example.com');\ncheckPublicSuffix(null, null);\ncheckPublicSuffix('com');");
+        let case = Case {
+            line: 3,
+            input: Some("WwW.example.COM"),
+            expected: Some("example.com"),
+        };
+        assert_eq!(cases.next(), Some(Ok(case)));
+        let case = Case {
+            line: 4,
+            input: None,
+            expected: None,
+        };
+        assert_eq!(cases.next(), Some(Ok(case)));
+        assert_eq!(cases.next(), Some(Err(Error { line: 5 })));
+        assert_eq!(cases.next(), None);
+    }
+
+    #[test]
+    fn report() {
+        let report = run_with(
+            &Tld,
+            "checkPublicSuffix('WwW.example.COM', 'example.com');\ncheckPublicSuffix('b.example.co.uk', 'example.co.uk');\ncheckPublicSuffix(null, null);",
+        );
+        assert!(!report.is_ok());
+        assert_eq!(report.passed().count(), 1);
+        assert_eq!(report.skipped().len(), 1);
+        let failed = report.failed().next().expect("failed case");
+        assert_eq!(failed.case.line, 2);
+        assert_eq!(failed.actual.expect("domain name"), "co.uk");
+        assert_eq!(
+            failed.to_string(),
+            "line 2: checkPublicSuffix('b.example.co.uk', 'example.co.uk'); returned 'co.uk'"
+        );
+    }
+
+    #[test]
+    fn run_upstream() {
+        let report = run(&Tld);
+        assert!(report.errors().is_empty());
+        assert_eq!(report.skipped().len(), 1);
+        assert!(report.failed().count() > 0);
+    }
+}
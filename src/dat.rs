@@ -0,0 +1,428 @@
+//! Parser for the `public_suffix_list.dat` format
+//!
+//! The format is described at <https://publicsuffix.org/list/>. Each
+//! line holds at most one rule, is only read up to the first whitespace
+//! and lines starting with `//` are comments. Rules are grouped into
+//! the ICANN and private sections using special comments.
+
+use crate::{Kind, Type};
+use core::fmt;
+use core::iter::Enumerate;
+use core::str::Lines;
+
+const BEGIN_ICANN: &str = "===BEGIN ICANN DOMAINS===";
+const END_ICANN: &str = "===END ICANN DOMAINS===";
+const BEGIN_PRIVATE: &str = "===BEGIN PRIVATE DOMAINS===";
+const END_PRIVATE: &str = "===END PRIVATE DOMAINS===";
+const SUBMITTED_BY: &str = "Submitted by ";
+
+/// A rule as written in a list source file
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Entry<'a> {
+    /// The labels of the rule without any `*.` or `!` prefix
+    pub name: &'a str,
+    /// Whether this is a normal, wildcard or exception rule
+    pub kind: Kind,
+    /// The section the rule is in
+    pub typ: Type,
+    /// The line the rule is on, starting at 1
+    pub line: usize,
+    /// The column the rule starts at, starting at 1
+    pub column: usize,
+    /// The comment above the group of rules this rule is in
+    pub comment: Option<Comment<'a>>,
+}
+
+/// The comment above a group of rules
+///
+/// Rules are grouped by blank lines. In the private section the comment
+/// names the organisation that operates the suffixes, often followed by
+/// its website, and who submitted them, for example:
+///
+/// ```text
+/// // Google, Inc. : https://www.google.com
+/// // Submitted by Eduardo Vela <evn@google.com>
+/// blogspot.com
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Comment<'a> {
+    pub(crate) src: &'a str,
+    /// The section the comment is in
+    pub(crate) typ: Type,
+}
+
+impl<'a> Comment<'a> {
+    /// The comment as written in the list source, including the `//` markers
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &'a str {
+        self.src
+    }
+
+    /// The text of each line of the comment without the `//` marker
+    pub fn lines(&self) -> impl Iterator<Item = &'a str> {
+        self.src
+            .lines()
+            .map(|line| line.trim().trim_start_matches('/').trim())
+    }
+
+    /// The organisation behind the rules, from the first line of the comment
+    ///
+    /// A ` : ` and the website after it are left out. ICANN comments name
+    /// the registry or just the TLD and a reference, so this is `None` for
+    /// rules in the ICANN section.
+    #[must_use]
+    pub fn organisation(&self) -> Option<&'a str> {
+        if self.typ == Type::Icann {
+            return None;
+        }
+        let line = self.lines().next()?;
+        let name = match line.find(" : ") {
+            Some(end) => line[..end].trim_end(),
+            None => line,
+        };
+        Some(name).filter(|name| !name.is_empty())
+    }
+
+    /// The section of the list the comment is in
+    #[inline]
+    #[must_use]
+    pub const fn typ(&self) -> Type {
+        self.typ
+    }
+
+    /// Who submitted the rules, from a `Submitted by` line
+    #[must_use]
+    pub fn submitter(&self) -> Option<&'a str> {
+        self.lines().find_map(|line| {
+            let prefix = line.get(..SUBMITTED_BY.len())?;
+            if prefix.eq_ignore_ascii_case(SUBMITTED_BY) {
+                Some(line[SUBMITTED_BY.len()..].trim())
+            } else {
+                None
+            }
+        })
+    }
+}
+
+/// An error found in a list source file
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Error {
+    /// The line the error is on, starting at 1
+    pub line: usize,
+    /// The column the error is at, starting at 1
+    pub column: usize,
+    /// What is wrong with the line
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.kind
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// The kind of error found in a list source file
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum ErrorKind {
+    /// A label is empty or contains a character other than a lowercase
+    /// ASCII letter, a digit, a `-` or a non-ASCII character
+    InvalidLabel,
+    /// A `!` appears anywhere other than at the start of the rule
+    MisplacedException,
+    /// A `*` appears anywhere other than as the leftmost label
+    MisplacedWildcard,
+    /// A rule is not inside an ICANN or private section
+    OutsideSection,
+    /// The same rule appears more than once, or a name has both a normal
+    /// and an exception rule
+    DuplicateRule,
+    /// An exception rule has no wildcard rule that it is an exception to
+    UncoveredException,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ErrorKind::InvalidLabel => "invalid label",
+            ErrorKind::MisplacedException => "`!` is only allowed at the start of a rule",
+            ErrorKind::MisplacedWildcard => "`*` is only allowed as the leftmost label",
+            ErrorKind::OutsideSection => "rule is outside the ICANN and private sections",
+            ErrorKind::DuplicateRule => "duplicate rule",
+            ErrorKind::UncoveredException => "exception rule without a covering wildcard rule",
+        })
+    }
+}
+
+/// An iterator over the rules in a list source file
+///
+/// Each rule is checked on its own so this does not detect duplicate rules
+/// or exceptions without a wildcard rule. [`Trie::try_parse`] checks for those.
+///
+/// [`Trie::try_parse`]: ../trie/struct.Trie.html#method.try_parse
+#[derive(Clone, Debug)]
+pub struct Entries<'a> {
+    src: &'a str,
+    lines: Enumerate<Lines<'a>>,
+    section: Option<Type>,
+    /// The byte range of the comment above the current group of rules
+    comment: Option<(usize, usize)>,
+    /// Whether the previous line was a rule, so a comment starts a new group
+    after_rule: bool,
+}
+
+impl<'a> Iterator for Entries<'a> {
+    type Item = Result<Entry<'a>, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for (index, line) in &mut self.lines {
+            let trimmed = line.trim();
+            if trimmed.starts_with("//") {
+                match trimmed[2..].trim() {
+                    BEGIN_ICANN => self.section = Some(Type::Icann),
+                    BEGIN_PRIVATE => self.section = Some(Type::Private),
+                    END_ICANN | END_PRIVATE => self.section = None,
+                    _ => {
+                        let start = offset(self.src, line);
+                        let end = start + line.len();
+                        self.comment = match self.comment {
+                            Some((first, _)) if !self.after_rule => Some((first, end)),
+                            _ => Some((start, end)),
+                        };
+                        self.after_rule = false;
+                        continue;
+                    }
+                }
+                self.comment = None;
+                self.after_rule = false;
+                continue;
+            }
+            let rule = match trimmed.split_whitespace().next() {
+                Some(rule) => rule,
+                None => {
+                    self.comment = None;
+                    self.after_rule = false;
+                    continue;
+                }
+            };
+            self.after_rule = true;
+            let start = line.len() - line.trim_start().len();
+            let error = |kind, offset: usize| Error {
+                line: index + 1,
+                column: column(line, start + offset),
+                kind,
+            };
+            let typ = match self.section {
+                Some(typ) => typ,
+                None => return Some(Err(error(ErrorKind::OutsideSection, 0))),
+            };
+            let (name, kind) = if rule.starts_with('!') {
+                (&rule[1..], Kind::Exception)
+            } else if rule.starts_with("*.") {
+                (&rule[2..], Kind::Wildcard)
+            } else {
+                (rule, Kind::Normal)
+            };
+            if let Err((kind, offset)) = validate(name) {
+                return Some(Err(error(kind, rule.len() - name.len() + offset)));
+            }
+            return Some(Ok(Entry {
+                name,
+                kind,
+                typ,
+                line: index + 1,
+                column: column(line, start),
+                comment: self.comment.map(|(start, end)| Comment {
+                    src: &self.src[start..end],
+                    typ,
+                }),
+            }));
+        }
+        None
+    }
+}
+
+/// Parses the rules in `src`, which must be in the `public_suffix_list.dat` format
+#[must_use]
+pub fn entries(src: &str) -> Entries<'_> {
+    Entries {
+        src,
+        lines: src.lines().enumerate(),
+        section: None,
+        comment: None,
+        after_rule: false,
+    }
+}
+
+/// Checks the labels of a rule, returning the byte offset of the first problem
+fn validate(name: &str) -> Result<(), (ErrorKind, usize)> {
+    let mut offset = 0;
+    for label in name.split('.') {
+        if label.is_empty() {
+            return Err((ErrorKind::InvalidLabel, offset));
+        }
+        for (index, c) in label.char_indices() {
+            let kind = match c {
+                'a'..='z' | '0'..='9' | '-' => continue,
+                '!' => ErrorKind::MisplacedException,
+                '*' => ErrorKind::MisplacedWildcard,
+                _ if !c.is_ascii() => continue,
+                _ => ErrorKind::InvalidLabel,
+            };
+            return Err((kind, offset + index));
+        }
+        offset += label.len() + 1;
+    }
+    Ok(())
+}
+
+/// The byte offset of `line` in `src`, which it must be a slice of
+fn offset(src: &str, line: &str) -> usize {
+    line.as_ptr() as usize - src.as_ptr() as usize
+}
+
+/// The column of the character at byte `offset`, starting at 1
+fn column(line: &str, offset: usize) -> usize {
+    line[..offset].chars().count() + 1
+}
+
+#[cfg(test)]
+mod test {
+    use super::{entries, Comment, Entry, Error, ErrorKind};
+    use crate::{Kind, Type};
+
+    const SRC: &str = "\
+// comment
+// ===BEGIN ICANN DOMAINS===
+
+// ck : https://en.wikipedia.org/wiki/.ck
+*.ck
+!www.ck
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+blogspot.com    trailing text is ignored
+// ===END PRIVATE DOMAINS===
+";
+
+    #[test]
+    fn sections() {
+        let mut entries = entries(SRC);
+        let entry = Entry {
+            name: "ck",
+            kind: Kind::Wildcard,
+            typ: Type::Icann,
+            line: 5,
+            column: 1,
+            comment: Some(Comment {
+                src: "// ck : https://en.wikipedia.org/wiki/.ck",
+                typ: Type::Icann,
+            }),
+        };
+        assert_eq!(entries.next(), Some(Ok(entry)));
+        let entry = Entry {
+            name: "www.ck",
+            kind: Kind::Exception,
+            typ: Type::Icann,
+            line: 6,
+            column: 1,
+            comment: Some(Comment {
+                src: "// ck : https://en.wikipedia.org/wiki/.ck",
+                typ: Type::Icann,
+            }),
+        };
+        assert_eq!(entries.next(), Some(Ok(entry)));
+        let entry = Entry {
+            name: "blogspot.com",
+            kind: Kind::Normal,
+            typ: Type::Private,
+            line: 9,
+            column: 1,
+            comment: None,
+        };
+        assert_eq!(entries.next(), Some(Ok(entry)));
+        assert_eq!(entries.next(), None);
+    }
+
+    #[test]
+    fn icann_comments() {
+        let comment = entries(SRC).next().and_then(|entry| entry.ok()?.comment);
+        let comment = comment.expect("comment");
+        assert_eq!(comment.typ(), Type::Icann);
+        assert_eq!(comment.organisation(), None);
+    }
+
+    #[test]
+    fn comments() {
+        let src = "\
+// ===BEGIN PRIVATE DOMAINS===
+// Google, Inc.
+// Submitted by Eduardo Vela <evn@google.com>
+blogspot.com
+blogspot.co.uk
+
+// no submitter
+github.io
+// rules after a rule start a new group
+githubusercontent.com
+
+// Cloudflare, Inc. : https://www.cloudflare.com/
+pages.dev
+
+workers.dev
+";
+        let comments: [Option<(&str, Option<&str>)>; 6] = [
+            Some(("Google, Inc.", Some("Eduardo Vela <evn@google.com>"))),
+            Some(("Google, Inc.", Some("Eduardo Vela <evn@google.com>"))),
+            Some(("no submitter", None)),
+            Some(("rules after a rule start a new group", None)),
+            Some(("Cloudflare, Inc.", None)),
+            None,
+        ];
+        let found = entries(src).map(|entry| {
+            let comment = entry.expect("entry").comment?;
+            Some((comment.organisation()?, comment.submitter()))
+        });
+        assert!(found.eq(comments.iter().copied()));
+
+        let comment = entries(src).next().and_then(|entry| entry.ok()?.comment);
+        assert_eq!(
+            comment.map(|comment| comment.as_str()),
+            Some("// Google, Inc.\n// Submitted by Eduardo Vela <evn@google.com>")
+        );
+    }
+
+    #[test]
+    fn errors() {
+        extern crate std;
+        use std::format;
+
+        let error = |line: &str| {
+            let src = format!("// ===BEGIN ICANN DOMAINS===\n{}", line);
+            let error = entries(&src).next().and_then(Result::err)?;
+            assert_eq!(error.line, 2);
+            Some((error.column, error.kind))
+        };
+        assert_eq!(error("co.uk"), None);
+        assert_eq!(error("公司.cn"), None);
+        assert_eq!(error("Co.uk"), Some((1, ErrorKind::InvalidLabel)));
+        assert_eq!(error("  co..uk"), Some((6, ErrorKind::InvalidLabel)));
+        assert_eq!(error("公司.c_n"), Some((5, ErrorKind::InvalidLabel)));
+        assert_eq!(error("www.!ck"), Some((5, ErrorKind::MisplacedException)));
+        assert_eq!(error("!*.ck"), Some((2, ErrorKind::MisplacedWildcard)));
+        assert_eq!(error("foo.*.ck"), Some((5, ErrorKind::MisplacedWildcard)));
+
+        let error = Error {
+            line: 1,
+            column: 1,
+            kind: ErrorKind::OutsideSection,
+        };
+        assert_eq!(entries("co.uk").next(), Some(Err(error)));
+    }
+}
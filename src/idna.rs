@@ -0,0 +1,208 @@
+//! Lookups that treat punycode and Unicode labels the same
+//!
+//! Some lists only contain Unicode labels (`рф`) and others only
+//! contain punycode ones (`xn--p1ai`). [`Idna`] converts each label to the
+//! form the inner list expects so either spelling resolves the same way.
+//!
+//! Labels are only converted between the two spellings. They are not
+//! mapped or validated according to UTS #46.
+
+use crate::{punycode, Info, List, Match, Metadata};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::vec::Vec;
+
+const ACE_PREFIX: &str = "xn--";
+
+/// The form of internationalised labels a list expects
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Form {
+    /// Unicode labels like `рф`
+    Unicode,
+    /// Punycode labels like `xn--p1ai`
+    Ascii,
+}
+
+impl Form {
+    /// Converts `label` to this form, returning it unchanged if it can't be converted
+    fn convert<'a>(self, label: &'a [u8]) -> Cow<'a, [u8]> {
+        let converted = match (self, core::str::from_utf8(label)) {
+            (Form::Unicode, Ok(label)) => label
+                .get(..ACE_PREFIX.len())
+                .filter(|prefix| prefix.eq_ignore_ascii_case(ACE_PREFIX))
+                .and_then(|_| punycode::decode(&label[ACE_PREFIX.len()..])),
+            (Form::Ascii, Ok(label)) if !label.is_ascii() => {
+                punycode::encode(label).map(|encoded| format!("{}{}", ACE_PREFIX, encoded))
+            }
+            _ => None,
+        };
+        match converted {
+            Some(converted) => Cow::Owned(converted.into_bytes()),
+            None => Cow::Borrowed(label),
+        }
+    }
+}
+
+/// Converts labels to the form the inner list expects before looking them up
+///
+/// The suffix length the inner list finds is mapped back onto the labels
+/// of the input so the returned suffixes and domains keep the spelling
+/// used by the caller.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Idna<L> {
+    list: L,
+    form: Form,
+}
+
+impl<L> Idna<L> {
+    /// Wraps a list that expects labels in the given form
+    #[inline]
+    #[must_use]
+    pub const fn new(list: L, form: Form) -> Self {
+        Idna { list, form }
+    }
+
+    /// Wraps a list that only contains Unicode labels
+    #[inline]
+    #[must_use]
+    pub const fn unicode(list: L) -> Self {
+        Self::new(list, Form::Unicode)
+    }
+
+    /// Wraps a list that only contains punycode labels
+    #[inline]
+    #[must_use]
+    pub const fn ascii(list: L) -> Self {
+        Self::new(list, Form::Ascii)
+    }
+
+    /// Returns the inner list
+    #[inline]
+    pub fn into_inner(self) -> L {
+        self.list
+    }
+}
+
+impl<L: List> List for Idna<L> {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.find_match(labels).info
+    }
+
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let mut lens = Vec::new();
+        let mut converted = Vec::new();
+        for label in labels {
+            lens.push(label.len());
+            converted.push(self.form.convert(label));
+        }
+        let mut found = self.list.find_match(converted.iter().map(|label| &**label));
+        found.info.len = original_len(found.info.len, &converted, &lens);
+        found
+    }
+
+    fn find_metadata<'a, T>(&self, labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let converted: Vec<_> = labels.map(|label| self.form.convert(label)).collect();
+        self.list
+            .find_metadata(converted.iter().map(|label| &**label))
+    }
+}
+
+/// Maps a suffix length over the converted labels back onto the original ones
+fn original_len(len: usize, converted: &[Cow<'_, [u8]>], lens: &[usize]) -> usize {
+    let mut converted_len = 0;
+    let mut original_len = 0;
+    for (index, (label, label_len)) in converted.iter().zip(lens).enumerate() {
+        if converted_len >= len {
+            break;
+        }
+        if index > 0 {
+            converted_len += 1;
+            original_len += 1;
+        }
+        converted_len += label.len();
+        original_len += label_len;
+    }
+    original_len
+}
+
+#[cfg(test)]
+mod test {
+    use super::Idna;
+    use crate::trie::Trie;
+    use crate::List;
+
+    const UNICODE: &str = "\
+// ===BEGIN ICANN DOMAINS===
+cn
+公司.cn
+中国
+рф
+// ===END ICANN DOMAINS===
+";
+
+    const ASCII: &str = "\
+// ===BEGIN ICANN DOMAINS===
+cn
+xn--55qx5d.cn
+xn--fiqs8s
+xn--p1ai
+// ===END ICANN DOMAINS===
+";
+
+    #[test]
+    fn unicode_list() {
+        let list = Idna::unicode(Trie::parse(UNICODE));
+        let domain = list
+            .domain(b"www.xn--85x722f.xn--55qx5d.cn")
+            .expect("domain name");
+        assert_eq!(domain, "xn--85x722f.xn--55qx5d.cn");
+        assert_eq!(domain.suffix(), "xn--55qx5d.cn");
+        assert!(domain.suffix().is_known());
+
+        let domain = list
+            .domain("пример.xn--p1ai.".as_bytes())
+            .expect("domain name");
+        assert_eq!(domain, "пример.xn--p1ai.");
+        assert!(domain.suffix().is_known());
+    }
+
+    #[test]
+    fn ascii_list() {
+        let list = Idna::ascii(Trie::parse(ASCII));
+        let domain = list
+            .domain("www.食狮.公司.cn".as_bytes())
+            .expect("domain name");
+        assert_eq!(domain, "食狮.公司.cn");
+        assert_eq!(domain.suffix(), "公司.cn");
+        assert!(domain.suffix().is_known());
+
+        let suffix = list.suffix("рф".as_bytes()).expect("suffix");
+        assert_eq!(suffix, "рф");
+        assert!(suffix.is_known());
+    }
+
+    #[test]
+    fn same_answer_for_both_spellings() {
+        for list in [
+            Idna::unicode(Trie::parse(UNICODE)),
+            Idna::ascii(Trie::parse(ASCII)),
+        ]
+        .iter()
+        {
+            let unicode = list.suffix("食狮.中国".as_bytes()).expect("suffix");
+            let ascii = list.suffix(b"xn--85x722f.xn--fiqs8s").expect("suffix");
+            assert_eq!(unicode.typ(), ascii.typ());
+            assert_eq!(unicode.labels().count(), ascii.labels().count());
+        }
+    }
+}
@@ -2,17 +2,54 @@
 //!
 //! The types in this crate assume that the input is valid
 //! UTF-8 encoded domain names. If input is potentially invalid,
-//! use a higher level crate like the `addr` crate.
+//! use [`List::try_suffix`] and [`List::try_domain`], which check
+//! the name first, or a higher level crate like the `addr` crate.
 //!
 //! Some implentations may also assume that the domain name is
 //! in lowercase and/or may only support looking up unicode
-//! domain names.
+//! domain names. Wrap such a list in [`case::CaseInsensitive`]
+//! to look up mixed-case names.
+//!
+//! [`case::CaseInsensitive`]: case/struct.CaseInsensitive.html
+//! [`List::try_suffix`]: trait.List.html#method.try_suffix
+//! [`List::try_domain`]: trait.List.html#method.try_domain
 
 #![no_std]
 #![forbid(unsafe_code)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
+pub mod canonical;
+pub mod case;
+pub mod compiled;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub mod dat;
+pub mod host;
+#[cfg(feature = "idna")]
+pub mod idna;
+pub mod overlay;
+#[cfg(feature = "alloc")]
+pub mod owned;
+#[cfg(feature = "idna")]
+mod punycode;
+#[cfg(feature = "std")]
+pub mod reload;
+#[cfg(feature = "serde")]
+mod serde_impls;
+pub mod tls;
+#[cfg(feature = "alloc")]
+pub mod trie;
+mod walk;
+
 use core::cmp::Ordering;
+use core::fmt;
 use core::hash::{Hash, Hasher};
+use core::ops::Range;
+use core::slice::{RSplit, Split};
 
 /// A list of all public suffixes
 pub trait List {
@@ -23,44 +60,388 @@ pub trait List {
     where
         T: Iterator<Item = &'a [u8]>;
 
-    /// Get the public suffix of the domain
+    /// Finds the suffix information of the given input labels along with
+    /// the kind of rule that matched
+    ///
+    /// The default implementation can only tell an explicit rule apart from
+    /// the implicit `*` one so it reports every known suffix as a normal rule.
+    /// Implementations that know about wildcards and exceptions should
+    /// override this.
+    ///
+    /// *NB:* `labels` must be in reverse order
     #[inline]
-    fn suffix<'a>(&self, name: &'a [u8]) -> Option<Suffix<'a>> {
-        let mut labels = name.rsplit(|x| *x == b'.');
-        let fqdn = if name.ends_with(b".") {
-            labels.next();
-            true
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let info = self.find(labels);
+        let kind = if info.typ.is_some() {
+            Kind::Normal
         } else {
-            false
+            Kind::Implicit
         };
-        let Info { mut len, typ } = self.find(labels);
-        if fqdn {
-            len += 1;
+        Match { info, kind }
+    }
+
+    /// Finds the suffix information of many domain names at once
+    ///
+    /// This is what [`List::suffixes`] and [`List::domains`] use. The
+    /// default implementation calls `find` for each name. Implementations
+    /// can override it to share work between names. Unlike `find`, this
+    /// takes whole names and a trailing `.` must not count as a label.
+    ///
+    /// [`List::suffixes`]: #method.suffixes
+    /// [`List::domains`]: #method.domains
+    ///
+    /// # Panics
+    ///
+    /// Panics if `names` and `out` have different lengths.
+    #[inline]
+    fn find_batch(&self, names: &[&[u8]], out: &mut [Info]) {
+        assert_eq!(names.len(), out.len(), "one output slot per name");
+        for (name, info) in names.iter().zip(out) {
+            *info = self.find(reversed_labels(name).0);
         }
-        if len == 0 {
-            return None;
+    }
+
+    /// Finds every known suffix of the given input labels
+    ///
+    /// The suffixes are written to `out` from the shortest to the longest
+    /// and the number written is returned. Lookups stop when `out` is full.
+    /// A suffix is included if the list would return it for some name made
+    /// of a part of `labels`, so rules overridden by an exception rule are
+    /// left out.
+    ///
+    /// The default implementation calls `find` with one more label at a
+    /// time. Implementations that can find all of them in one pass should
+    /// override this.
+    ///
+    /// *NB:* `labels` must be in reverse order
+    #[inline]
+    fn find_all<'a, T>(&self, labels: T, out: &mut [Info]) -> usize
+    where
+        T: Iterator<Item = &'a [u8]> + Clone,
+    {
+        let mut count = 0;
+        let mut len = 0;
+        for (index, label) in labels.clone().enumerate() {
+            if count == out.len() {
+                break;
+            }
+            len += usize::from(index > 0) + label.len();
+            let info = self.find(labels.clone().take(index + 1));
+            if info.typ.is_some() && info.len == len {
+                out[count] = info;
+                count += 1;
+            }
+        }
+        count
+    }
+
+    /// Finds the suffix information after adding one label to a lookup
+    ///
+    /// `labels` are the labels looked up so far in reverse order, ending
+    /// with the new one, and `prev` is what this returned for the labels
+    /// before it, or `None` for the first label. This is what [`Cursor`]
+    /// uses.
+    ///
+    /// The default implementation looks up all of `labels` with
+    /// `find_match` and leaves [`Step::node`] as it was. Implementations
+    /// that can continue from where `prev` stopped should override this.
+    ///
+    /// [`Cursor`]: struct.Cursor.html
+    /// [`Step::node`]: struct.Step.html#structfield.node
+    #[inline]
+    fn find_step(&self, labels: &[&[u8]], prev: Option<Step>) -> Step {
+        Step {
+            found: self.find_match(labels.iter().copied()),
+            node: prev.and_then(|prev| prev.node),
+        }
+    }
+
+    /// Finds the metadata of the rule matching the given input labels
+    ///
+    /// The default implementation returns `None`. Implementations that keep
+    /// track of where their rules came from should override this.
+    ///
+    /// *NB:* `labels` must be in reverse order
+    #[inline]
+    fn find_metadata<'a, T>(&self, _labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        None
+    }
+
+    /// Get the public suffix of the domain
+    #[inline]
+    fn suffix<'a>(&self, name: &'a [u8]) -> Option<Suffix<'a>> {
+        let (labels, fqdn) = reversed_labels(name);
+        let info = self.find(labels);
+        suffix_from_info(name, fqdn, info)
+    }
+
+    /// Get the public suffixes of many domain names at once
+    ///
+    /// # Panics
+    ///
+    /// Panics if `names` and `out` have different lengths.
+    #[inline]
+    fn suffixes<'a>(&self, names: &[&'a [u8]], out: &mut [Option<Suffix<'a>>]) {
+        assert_eq!(names.len(), out.len(), "one output slot per name");
+        let mut infos = [Info { len: 0, typ: None }; BATCH_LEN];
+        for (names, out) in names.chunks(BATCH_LEN).zip(out.chunks_mut(BATCH_LEN)) {
+            let infos = &mut infos[..names.len()];
+            self.find_batch(names, infos);
+            for ((name, info), suffix) in names.iter().zip(infos.iter()).zip(out) {
+                *suffix = suffix_from_info(name, name.ends_with(b"."), *info);
+            }
+        }
+    }
+
+    /// Get the registrable domains of many domain names at once
+    ///
+    /// # Panics
+    ///
+    /// Panics if `names` and `out` have different lengths.
+    #[inline]
+    fn domains<'a>(&self, names: &[&'a [u8]], out: &mut [Option<Domain<'a>>]) {
+        assert_eq!(names.len(), out.len(), "one output slot per name");
+        let mut suffixes = [None; BATCH_LEN];
+        for (names, out) in names.chunks(BATCH_LEN).zip(out.chunks_mut(BATCH_LEN)) {
+            let suffixes = &mut suffixes[..names.len()];
+            self.suffixes(names, suffixes);
+            for ((name, suffix), domain) in names.iter().zip(suffixes.iter()).zip(out) {
+                *domain = match suffix {
+                    Some(suffix) => domain_from_suffix(name, *suffix),
+                    None => None,
+                };
+            }
+        }
+    }
+
+    /// Get the rule that determined the public suffix of the domain
+    #[inline]
+    fn rule<'a>(&self, name: &'a [u8]) -> Option<Rule<'a>> {
+        let (labels, fqdn) = reversed_labels(name);
+        let Match { info, kind } = self.find_match(labels);
+        let suffix = suffix_from_info(name, fqdn, info)?;
+        Some(Rule::from_suffix(name, suffix, kind))
+    }
+
+    /// Start a lookup that takes one label at a time from the right
+    ///
+    /// See [`Cursor`] for how this shares work between lookups of names
+    /// with the same parent.
+    ///
+    /// [`Cursor`]: struct.Cursor.html
+    #[inline]
+    fn cursor<'a>(&self) -> Cursor<'_, 'a, Self> {
+        Cursor {
+            list: self,
+            labels: [&[]; MAX_LABELS],
+            steps: [Step::default(); MAX_LABELS],
+            depth: 0,
+        }
+    }
+
+    /// Get every known suffix of the domain, from the shortest to the longest
+    ///
+    /// For `a.b.c.appspot.com` this yields `com` and `appspot.com`, then
+    /// `c.appspot.com` if the list has a rule for it, and so on.
+    #[inline]
+    fn all_suffixes<'a>(&self, name: &'a [u8]) -> AllSuffixes<'a> {
+        let (labels, fqdn) = reversed_labels(name);
+        let mut infos = [Info { len: 0, typ: None }; MAX_LABELS];
+        let len = self.find_all(labels, &mut infos);
+        AllSuffixes {
+            name,
+            fqdn,
+            infos,
+            range: 0..len,
         }
-        let offset = name.len() - len;
-        let bytes = name.get(offset..)?;
-        Some(Suffix { bytes, fqdn, typ })
+    }
+
+    /// Get the public suffix of a domain name given as a string
+    #[inline]
+    fn suffix_str<'a>(&self, name: &'a str) -> Option<SuffixStr<'a>> {
+        let suffix = self.suffix(name.as_bytes())?;
+        SuffixStr::new(name, suffix)
+    }
+
+    /// Get the registrable domain of a domain name given as a string
+    #[inline]
+    fn domain_str<'a>(&self, name: &'a str) -> Option<DomainStr<'a>> {
+        let domain = self.domain(name.as_bytes())?;
+        DomainStr::new(name, domain)
+    }
+
+    /// Get the metadata of the rule that determined the public suffix of the domain
+    ///
+    /// This is `None` when the implicit `*` rule matched or when the list
+    /// does not keep metadata.
+    #[inline]
+    fn metadata(&self, name: &[u8]) -> Option<Metadata<'_>> {
+        self.find_metadata(reversed_labels(name).0)
     }
 
     /// Get the registrable domain
     #[inline]
     fn domain<'a>(&self, name: &'a [u8]) -> Option<Domain<'a>> {
         let suffix = self.suffix(name)?;
-        let name_len = name.len();
-        let suffix_len = suffix.bytes.len();
-        if name_len < suffix_len + 2 {
-            return None;
+        domain_from_suffix(name, suffix)
+    }
+
+    /// Get the public suffix of the domain using the given lookup options
+    #[inline]
+    fn suffix_with<'a>(&self, name: &'a [u8], options: Options) -> Option<Suffix<'a>> {
+        let (labels, fqdn) = reversed_labels(name);
+        let mut info = self.find(labels);
+        if !options.private {
+            info = icann_fallback(self, name, info);
+        }
+        if info.typ.is_none() {
+            match options.default_rule {
+                DefaultRule::Implicit => {}
+                DefaultRule::Reject => return None,
+                DefaultRule::Unregistrable => info.len = strip_dot(name).len(),
+            }
+        }
+        suffix_from_info(name, fqdn, info)
+    }
+
+    /// Get the registrable domain using the given lookup options
+    #[inline]
+    fn domain_with<'a>(&self, name: &'a [u8], options: Options) -> Option<Domain<'a>> {
+        let suffix = self.suffix_with(name, options)?;
+        domain_from_suffix(name, suffix)
+    }
+
+    /// Checks the `Domain` attribute of a cookie set by `host` as described
+    /// in [RFC 6265 section 5.3]
+    ///
+    /// `domain` is the attribute value, with or without a leading `.`. A
+    /// trailing `.` on `host` is ignored. Names are compared ignoring ASCII
+    /// case, but the suffix lookup is only case-insensitive if the list is,
+    /// so wrap case-sensitive lists in [`case::CaseInsensitive`].
+    ///
+    /// [RFC 6265 section 5.3]: https://www.rfc-editor.org/rfc/rfc6265#section-5.3
+    /// [`case::CaseInsensitive`]: case/struct.CaseInsensitive.html
+    #[inline]
+    fn cookie_domain<'a>(&self, host: &[u8], domain: &'a [u8]) -> CookieDomain<'a> {
+        let host = strip_dot(host);
+        let domain = if domain.starts_with(b".") {
+            &domain[1..]
+        } else {
+            domain
+        };
+        if domain.is_empty() {
+            return CookieDomain::HostOnly;
+        }
+        let is_suffix = self
+            .suffix(domain)
+            .filter(|suffix| suffix.as_bytes().len() == domain.len())
+            .is_some();
+        if is_suffix {
+            return if domain.eq_ignore_ascii_case(host) {
+                CookieDomain::HostOnly
+            } else {
+                CookieDomain::PublicSuffix
+            };
+        }
+        if domain_match(host, domain) {
+            CookieDomain::Domain(domain)
+        } else {
+            CookieDomain::Mismatch
+        }
+    }
+
+    /// Whether both names have the same registrable domain
+    ///
+    /// This is `false` if either name has no registrable domain, for
+    /// example because it is itself a public suffix. A trailing `.` on
+    /// either name is ignored.
+    #[inline]
+    fn same_registrable_domain(&self, a: &[u8], b: &[u8]) -> bool {
+        match (self.domain(a), self.domain(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Whether both hosts are [same site] as defined by the HTML standard
+    ///
+    /// Hosts are same site if they are the same host or if they have the
+    /// same registrable domain. Whether private suffixes like `github.io`
+    /// separate sites depends on the list, so hosts that are themselves
+    /// public suffixes are only same site with themselves. A trailing `.`
+    /// on either host is ignored.
+    ///
+    /// [same site]: https://html.spec.whatwg.org/multipage/browsers.html#same-site
+    #[inline]
+    fn same_site(&self, a: &[u8], b: &[u8]) -> bool {
+        strip_dot(a) == strip_dot(b) || self.same_registrable_domain(a, b)
+    }
+
+    /// Whether both names have the same registrable domain using the given
+    /// lookup options
+    ///
+    /// With `Options::new().private(false)` only ICANN suffixes separate
+    /// registrable domains, so `a.github.io` and `b.github.io` share
+    /// `github.io`.
+    #[inline]
+    fn same_registrable_domain_with(&self, a: &[u8], b: &[u8], options: Options) -> bool {
+        match (self.domain_with(a, options), self.domain_with(b, options)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
         }
-        let offset = name_len - (1 + suffix_len);
-        let subdomain = name.get(..offset)?;
-        let root_label = subdomain.rsplitn(2, |x| *x == b'.').next()?;
-        let registrable_len = root_label.len() + 1 + suffix_len;
-        let offset = name_len - registrable_len;
-        let bytes = name.get(offset..)?;
-        Some(Domain { bytes, suffix })
+    }
+
+    /// Whether both hosts are same site using the given lookup options
+    ///
+    /// See [`List::same_site`] and [`List::same_registrable_domain_with`].
+    ///
+    /// [`List::same_site`]: #method.same_site
+    /// [`List::same_registrable_domain_with`]: #method.same_registrable_domain_with
+    #[inline]
+    fn same_site_with(&self, a: &[u8], b: &[u8], options: Options) -> bool {
+        strip_dot(a) == strip_dot(b) || self.same_registrable_domain_with(a, b, options)
+    }
+
+    /// Get the public suffix of the domain, checking that it is a valid domain name first
+    ///
+    /// Unlike [`List::suffix`] this rejects empty labels, names and labels
+    /// that are too long and bytes that aren't allowed in domain names.
+    ///
+    /// This is `Ok(None)` for valid names the list has no suffix for, such
+    /// as when it returns an empty match.
+    ///
+    /// [`List::suffix`]: #method.suffix
+    #[inline]
+    fn try_suffix<'a>(&self, name: &'a [u8]) -> Result<Option<Suffix<'a>>, NameError> {
+        validate_name(name)?;
+        Ok(self.suffix(name))
+    }
+
+    /// Get the registrable domain, checking that it is a valid domain name first
+    ///
+    /// This is `Ok(None)` for valid names without a registrable domain,
+    /// such as names that are themselves public suffixes.
+    #[inline]
+    fn try_domain<'a>(&self, name: &'a [u8]) -> Result<Option<Domain<'a>>, NameError> {
+        validate_name(name)?;
+        Ok(self.domain(name))
+    }
+
+    /// Split the domain name into its subdomain, registrable domain and suffix
+    #[inline]
+    fn name<'a>(&self, name: &'a [u8]) -> Option<Name<'a>> {
+        let suffix = self.suffix(name)?;
+        Some(Name {
+            bytes: name,
+            suffix,
+            domain: domain_from_suffix(name, suffix),
+        })
     }
 }
 
@@ -72,6 +453,40 @@ impl<L: List> List for &'_ L {
     {
         (*self).find(labels)
     }
+
+    #[inline]
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        (*self).find_match(labels)
+    }
+
+    #[inline]
+    fn find_batch(&self, names: &[&[u8]], out: &mut [Info]) {
+        (*self).find_batch(names, out)
+    }
+
+    #[inline]
+    fn find_all<'a, T>(&self, labels: T, out: &mut [Info]) -> usize
+    where
+        T: Iterator<Item = &'a [u8]> + Clone,
+    {
+        (*self).find_all(labels, out)
+    }
+
+    #[inline]
+    fn find_step(&self, labels: &[&[u8]], prev: Option<Step>) -> Step {
+        (*self).find_step(labels, prev)
+    }
+
+    #[inline]
+    fn find_metadata<'a, T>(&self, labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        (*self).find_metadata(labels)
+    }
 }
 
 /// Type of suffix
@@ -81,6 +496,85 @@ pub enum Type {
     Private,
 }
 
+/// Options for looking up a domain name
+///
+/// ```rust
+/// # use psl_types::{Info, List, Options, Type};
+/// # struct Blogspot;
+/// # impl List for Blogspot {
+/// #     fn find<'a, T: Iterator<Item = &'a [u8]>>(&self, mut labels: T) -> Info {
+/// #         match (labels.next(), labels.next()) {
+/// #             (Some(b"com"), Some(b"blogspot")) => Info { len: 12, typ: Some(Type::Private) },
+/// #             (Some(label), _) => Info { len: label.len(), typ: Some(Type::Icann) },
+/// #             (None, _) => Info { len: 0, typ: None },
+/// #         }
+/// #     }
+/// # }
+/// # let list = Blogspot;
+/// // `blogspot.com` is in the private section
+/// let domain = list.domain_with(b"foo.blogspot.com", Options::new().private(false));
+/// assert_eq!(domain.unwrap(), "blogspot.com");
+/// ```
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Options {
+    private: bool,
+    default_rule: DefaultRule,
+}
+
+impl Options {
+    /// The options used by `List::suffix` and `List::domain`
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Options {
+            private: true,
+            default_rule: DefaultRule::Implicit,
+        }
+    }
+
+    /// Whether to use rules from the private section of the list
+    ///
+    /// When disabled, a name matching a private rule gets the longest
+    /// suffix matching an ICANN rule instead.
+    #[inline]
+    #[must_use]
+    pub const fn private(self, private: bool) -> Self {
+        Options { private, ..self }
+    }
+
+    /// What to do with names that no rule in the list matches
+    #[inline]
+    #[must_use]
+    pub const fn default_rule(self, default_rule: DefaultRule) -> Self {
+        Options {
+            default_rule,
+            ..self
+        }
+    }
+}
+
+impl Default for Options {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// How to handle names that no rule in the list matches
+///
+/// These are names with an unknown TLD like `printer.corp`.
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum DefaultRule {
+    /// Use the implicit `*` rule from the public suffix list algorithm,
+    /// making the last label the suffix (`corp`)
+    Implicit,
+    /// Return no suffix or registrable domain
+    Reject,
+    /// Make the whole name the suffix (`printer.corp`) so that it has
+    /// no registrable domain
+    Unregistrable,
+}
+
 /// Information about the suffix
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub struct Info {
@@ -88,6 +582,72 @@ pub struct Info {
     pub typ: Option<Type>,
 }
 
+/// Kind of rule that matched a suffix
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Kind {
+    /// A plain rule like `co.uk`
+    Normal,
+    /// A wildcard rule like `*.ck`
+    Wildcard,
+    /// An exception rule like `!www.ck`
+    Exception,
+    /// The implicit `*` rule used when no other rule matches
+    Implicit,
+}
+
+/// Information about the suffix and the kind of rule that produced it
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Match {
+    pub info: Info,
+    pub kind: Kind,
+}
+
+/// The state of a lookup after some of the labels
+///
+/// This is what [`List::find_step`] returns.
+///
+/// [`List::find_step`]: trait.List.html#method.find_step
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Step {
+    /// The match for the labels so far
+    pub found: Match,
+    /// Where the lookup is in the list, in a form only the list understands
+    ///
+    /// `None` if the list does not track this or no longer rule can match.
+    pub node: Option<usize>,
+}
+
+impl Default for Step {
+    #[inline]
+    fn default() -> Self {
+        Step {
+            found: Match {
+                info: Info { len: 0, typ: None },
+                kind: Kind::Implicit,
+            },
+            node: None,
+        }
+    }
+}
+
+/// Where a rule comes from and who operates it
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct Metadata<'a> {
+    /// The comment above the group of rules the rule is in
+    pub comment: Option<dat::Comment<'a>>,
+    /// The line the rule is on in the list source, starting at 1
+    pub line: Option<usize>,
+}
+
+impl<'a> Metadata<'a> {
+    /// The organisation that operates the suffix, if the comment names one
+    #[inline]
+    #[must_use]
+    pub fn organisation(&self) -> Option<&'a str> {
+        self.comment?.organisation()
+    }
+}
+
 /// The suffix of a domain name
 #[derive(Copy, Clone, Eq, Debug)]
 pub struct Suffix<'a> {
@@ -116,6 +676,18 @@ impl<'a> Suffix<'a> {
         self.bytes
     }
 
+    /// The suffix as a string, if it is valid UTF-8
+    ///
+    /// Use [`List::suffix_str`] to look up a `&str` and get one back without
+    /// checking it again.
+    ///
+    /// [`List::suffix_str`]: trait.List.html#method.suffix_str
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        core::str::from_utf8(self.bytes).ok()
+    }
+
     /// Whether or not the suffix is fully qualified (i.e. it ends with a `.`)
     #[inline]
     #[must_use]
@@ -141,6 +713,13 @@ impl<'a> Suffix<'a> {
         self
     }
 
+    /// The labels of the suffix
+    #[inline]
+    #[must_use]
+    pub fn labels(&self) -> Labels<'a> {
+        Labels::new(self.bytes)
+    }
+
     /// Whether or not this is a known suffix (i.e. it is explicitly in the public suffix list)
     // Could be const but Isahc needs support for Rust v1.41
     #[inline]
@@ -178,6 +757,7 @@ impl Ord for Suffix<'_> {
     }
 }
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Suffix<'_> {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -192,6 +772,13 @@ impl Hash for Suffix<'_> {
     }
 }
 
+impl fmt::Display for Suffix<'_> {
+    /// Writes the suffix, replacing invalid UTF-8 with `U+FFFD`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_lossy(f, self.bytes)
+    }
+}
+
 /// A registrable domain name
 #[derive(Copy, Clone, Eq, Debug)]
 pub struct Domain<'a> {
@@ -215,6 +802,18 @@ impl<'a> Domain<'a> {
         self.bytes
     }
 
+    /// The domain name as a string, if it is valid UTF-8
+    ///
+    /// Use [`List::domain_str`] to look up a `&str` and get one back without
+    /// checking it again.
+    ///
+    /// [`List::domain_str`]: trait.List.html#method.domain_str
+    #[inline]
+    #[must_use]
+    pub fn as_str(&self) -> Option<&'a str> {
+        core::str::from_utf8(self.bytes).ok()
+    }
+
     /// The public suffix of this domain name
     #[inline]
     #[must_use]
@@ -222,6 +821,13 @@ impl<'a> Domain<'a> {
         self.suffix
     }
 
+    /// The labels of the domain name
+    #[inline]
+    #[must_use]
+    pub fn labels(&self) -> Labels<'a> {
+        Labels::new(self.bytes)
+    }
+
     /// Returns the domain with a trailing `.` removed
     #[inline]
     #[must_use]
@@ -262,6 +868,7 @@ impl Ord for Domain<'_> {
     }
 }
 
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Domain<'_> {
     #[inline]
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -276,28 +883,802 @@ impl Hash for Domain<'_> {
     }
 }
 
-#[inline]
-fn strip_dot(bytes: &[u8]) -> &[u8] {
-    if bytes.ends_with(b".") {
-        &bytes[..bytes.len() - 1]
-    } else {
-        bytes
+impl fmt::Display for Domain<'_> {
+    /// Writes the domain name, replacing invalid UTF-8 with `U+FFFD`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write_lossy(f, self.bytes)
     }
 }
 
-#[cfg(test)]
-mod test {
-    use super::{Info, List as Psl};
+/// The suffix of a domain name looked up as a string
+///
+/// This is returned by [`List::suffix_str`].
+///
+/// [`List::suffix_str`]: trait.List.html#method.suffix_str
+#[derive(Copy, Clone, Eq, Debug)]
+pub struct SuffixStr<'a> {
+    text: &'a str,
+    suffix: Suffix<'a>,
+}
 
-    struct List;
+impl<'a> SuffixStr<'a> {
+    /// Pairs `suffix` with the end of `name` it was found in
+    ///
+    /// Returns `None` if the list returned a length that splits a character.
+    fn new(name: &'a str, suffix: Suffix<'a>) -> Option<Self> {
+        let text = name.get(name.len() - suffix.bytes.len()..)?;
+        Some(SuffixStr { text, suffix })
+    }
 
-    impl Psl for List {
-        fn find<'a, T>(&self, mut labels: T) -> Info
-        where
-            T: Iterator<Item = &'a [u8]>,
-        {
-            match labels.next() {
-                Some(label) => Info {
+    /// The suffix as a string
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// The suffix as bytes
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.text.as_bytes()
+    }
+
+    /// The suffix without the guarantee that it is a string
+    #[inline]
+    #[must_use]
+    pub const fn as_suffix(&self) -> Suffix<'a> {
+        self.suffix
+    }
+
+    /// Whether or not the suffix is fully qualified (i.e. it ends with a `.`)
+    #[inline]
+    #[must_use]
+    pub const fn is_fqdn(&self) -> bool {
+        self.suffix.fqdn
+    }
+
+    /// Whether this is an `ICANN`, `private` or unknown suffix
+    #[inline]
+    #[must_use]
+    pub const fn typ(&self) -> Option<Type> {
+        self.suffix.typ
+    }
+
+    /// Whether or not this is a known suffix (i.e. it is explicitly in the public suffix list)
+    #[inline]
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        self.suffix.is_known()
+    }
+
+    /// Returns the suffix with a trailing `.` removed
+    #[inline]
+    #[must_use]
+    pub fn trim(self) -> Self {
+        let suffix = self.suffix.trim();
+        SuffixStr {
+            text: &self.text[..suffix.bytes.len()],
+            suffix,
+        }
+    }
+}
+
+impl<'a> From<SuffixStr<'a>> for Suffix<'a> {
+    #[inline]
+    fn from(suffix: SuffixStr<'a>) -> Self {
+        suffix.suffix
+    }
+}
+
+impl PartialEq for SuffixStr<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.suffix == other.suffix
+    }
+}
+
+impl PartialEq<&str> for SuffixStr<'_> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.suffix == *other
+    }
+}
+
+impl Ord for SuffixStr<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.suffix.cmp(&other.suffix)
+    }
+}
+
+impl PartialOrd for SuffixStr<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for SuffixStr<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.suffix.hash(state);
+    }
+}
+
+impl fmt::Display for SuffixStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+/// A registrable domain name looked up as a string
+///
+/// This is returned by [`List::domain_str`].
+///
+/// [`List::domain_str`]: trait.List.html#method.domain_str
+#[derive(Copy, Clone, Eq, Debug)]
+pub struct DomainStr<'a> {
+    text: &'a str,
+    domain: Domain<'a>,
+}
+
+impl<'a> DomainStr<'a> {
+    /// Pairs `domain` with the end of `name` it was found in
+    ///
+    /// Returns `None` if the list returned a length that splits a character.
+    fn new(name: &'a str, domain: Domain<'a>) -> Option<Self> {
+        let text = name.get(name.len() - domain.bytes.len()..)?;
+        Some(DomainStr { text, domain })
+    }
+
+    /// The domain name as a string
+    #[inline]
+    #[must_use]
+    pub const fn as_str(&self) -> &'a str {
+        self.text
+    }
+
+    /// The domain name as bytes
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.text.as_bytes()
+    }
+
+    /// The domain name without the guarantee that it is a string
+    #[inline]
+    #[must_use]
+    pub const fn as_domain(&self) -> Domain<'a> {
+        self.domain
+    }
+
+    /// The public suffix of this domain name
+    #[inline]
+    #[must_use]
+    pub fn suffix(&self) -> SuffixStr<'a> {
+        let suffix = self.domain.suffix;
+        SuffixStr {
+            text: &self.text[self.text.len() - suffix.bytes.len()..],
+            suffix,
+        }
+    }
+
+    /// Returns the domain with a trailing `.` removed
+    #[inline]
+    #[must_use]
+    pub fn trim(self) -> Self {
+        let domain = self.domain.trim();
+        DomainStr {
+            text: &self.text[..domain.bytes.len()],
+            domain,
+        }
+    }
+}
+
+impl<'a> From<DomainStr<'a>> for Domain<'a> {
+    #[inline]
+    fn from(domain: DomainStr<'a>) -> Self {
+        domain.domain
+    }
+}
+
+impl PartialEq for DomainStr<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.domain == other.domain
+    }
+}
+
+impl PartialEq<&str> for DomainStr<'_> {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.domain == *other
+    }
+}
+
+impl Ord for DomainStr<'_> {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.domain.cmp(&other.domain)
+    }
+}
+
+impl PartialOrd for DomainStr<'_> {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for DomainStr<'_> {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.domain.hash(state);
+    }
+}
+
+impl fmt::Display for DomainStr<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.text)
+    }
+}
+
+/// An iterator over every known suffix of a domain name
+///
+/// This is returned by [`List::all_suffixes`].
+///
+/// [`List::all_suffixes`]: trait.List.html#method.all_suffixes
+#[derive(Clone)]
+pub struct AllSuffixes<'a> {
+    name: &'a [u8],
+    fqdn: bool,
+    infos: [Info; MAX_LABELS],
+    range: Range<usize>,
+}
+
+impl<'a> Iterator for AllSuffixes<'a> {
+    type Item = Suffix<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let info = self.infos[self.range.next()?];
+            if let Some(suffix) = suffix_from_info(self.name, self.fqdn, info) {
+                return Some(suffix);
+            }
+        }
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.range.len()))
+    }
+}
+
+impl fmt::Debug for AllSuffixes<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.clone()).finish()
+    }
+}
+
+/// A lookup that takes one label at a time from the right
+///
+/// Labels are pushed from the TLD down and popped again when moving on to
+/// a sibling, so walking a tree of names only looks up each label once:
+///
+/// ```rust
+/// # use psl_types::{Info, List};
+/// # struct Tld;
+/// # impl List for Tld {
+/// #     fn find<'a, T: Iterator<Item = &'a [u8]>>(&self, mut labels: T) -> Info {
+/// #         Info { len: labels.next().map_or(0, <[u8]>::len), typ: None }
+/// #     }
+/// # }
+/// # let list = Tld;
+/// let mut cursor = list.cursor();
+/// cursor.push(b"com");
+/// cursor.push(b"example");
+/// for sibling in [&b"a"[..], b"b"].iter() {
+///     let info = cursor.push(sibling).unwrap();
+///     assert_eq!(info.len, 3);
+///     assert_eq!(cursor.pop(), Some(*sibling));
+/// }
+/// assert_eq!(cursor.labels(), [&b"com"[..], b"example"]);
+/// ```
+///
+/// This is returned by [`List::cursor`].
+///
+/// [`List::cursor`]: trait.List.html#method.cursor
+pub struct Cursor<'l, 'a, L: ?Sized> {
+    list: &'l L,
+    labels: [&'a [u8]; MAX_LABELS],
+    steps: [Step; MAX_LABELS],
+    depth: usize,
+}
+
+impl<'a, L: List + ?Sized> Cursor<'_, 'a, L> {
+    /// Adds a label to the left of the name, returning the suffix information for the new name
+    ///
+    /// Returns `None` without adding the label if the name already has 128 labels.
+    #[inline]
+    pub fn push(&mut self, label: &'a [u8]) -> Option<Info> {
+        if self.depth == MAX_LABELS {
+            return None;
+        }
+        let prev = self.depth.checked_sub(1).map(|depth| self.steps[depth]);
+        self.labels[self.depth] = label;
+        self.steps[self.depth] = self.list.find_step(&self.labels[..=self.depth], prev);
+        self.depth += 1;
+        Some(self.info())
+    }
+
+    /// Removes the leftmost label of the name
+    #[inline]
+    pub fn pop(&mut self) -> Option<&'a [u8]> {
+        self.depth = self.depth.checked_sub(1)?;
+        Some(self.labels[self.depth])
+    }
+
+    /// The suffix information for the current name
+    #[inline]
+    #[must_use]
+    pub fn info(&self) -> Info {
+        self.current().info
+    }
+
+    /// The suffix information and kind of rule for the current name
+    #[inline]
+    #[must_use]
+    pub fn current(&self) -> Match {
+        match self.depth.checked_sub(1) {
+            Some(depth) => self.steps[depth].found,
+            None => Step::default().found,
+        }
+    }
+
+    /// The labels of the current name in reverse order
+    #[inline]
+    #[must_use]
+    pub fn labels(&self) -> &[&'a [u8]] {
+        &self.labels[..self.depth]
+    }
+}
+
+impl<L: ?Sized> fmt::Debug for Cursor<'_, '_, L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("labels", &&self.labels[..self.depth])
+            .field("steps", &&self.steps[..self.depth])
+            .finish()
+    }
+}
+
+/// A domain name split into its parts
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Name<'a> {
+    bytes: &'a [u8],
+    suffix: Suffix<'a>,
+    domain: Option<Domain<'a>>,
+}
+
+impl<'a> Name<'a> {
+    /// The full domain name as bytes
+    #[inline]
+    #[must_use]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The public suffix of the domain name
+    #[inline]
+    #[must_use]
+    pub const fn suffix(&self) -> Suffix<'a> {
+        self.suffix
+    }
+
+    /// The registrable domain, if the name is not itself a public suffix
+    #[inline]
+    #[must_use]
+    pub const fn domain(&self) -> Option<Domain<'a>> {
+        self.domain
+    }
+
+    /// The label to the left of the suffix (`example` in `www.example.com`)
+    #[inline]
+    #[must_use]
+    pub fn root(&self) -> Option<&'a [u8]> {
+        let domain = self.domain?;
+        Some(&domain.bytes[..domain.bytes.len() - domain.suffix.bytes.len() - 1])
+    }
+
+    /// The labels to the left of the registrable domain (`www` in `www.example.com`)
+    #[inline]
+    #[must_use]
+    pub fn subdomain(&self) -> Option<&'a [u8]> {
+        let domain_len = self.domain?.bytes.len();
+        if self.bytes.len() <= domain_len {
+            return None;
+        }
+        Some(&self.bytes[..self.bytes.len() - domain_len - 1])
+    }
+}
+
+/// An iterator over the labels of a domain name
+///
+/// Labels are yielded from left to right. Use `rev` to get them from
+/// right to left. A trailing `.` does not produce an empty label.
+#[derive(Clone, Debug)]
+pub struct Labels<'a> {
+    inner: Option<ForwardLabels<'a>>,
+}
+
+impl<'a> Labels<'a> {
+    #[inline]
+    fn new(bytes: &'a [u8]) -> Self {
+        let bytes = strip_dot(bytes);
+        let inner = if bytes.is_empty() {
+            None
+        } else {
+            Some(bytes.split(is_dot as fn(&u8) -> bool))
+        };
+        Labels { inner }
+    }
+}
+
+impl<'a> Iterator for Labels<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next()
+    }
+}
+
+impl DoubleEndedIterator for Labels<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner.as_mut()?.next_back()
+    }
+}
+
+/// The public suffix list rule that matched a domain name
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub struct Rule<'a> {
+    bytes: &'a [u8],
+    suffix: Suffix<'a>,
+    kind: Kind,
+}
+
+impl<'a> Rule<'a> {
+    /// Builds a rule from the suffix it produced for `name`
+    fn from_suffix(name: &'a [u8], suffix: Suffix<'a>, kind: Kind) -> Rule<'a> {
+        let name = strip_dot(name);
+        let labels = suffix.trim().bytes;
+        let bytes = match kind {
+            Kind::Normal => labels,
+            Kind::Wildcard => match labels.iter().position(|x| *x == b'.') {
+                Some(pos) => &labels[pos + 1..],
+                None => &labels[labels.len()..],
+            },
+            Kind::Exception => {
+                let prefix = &name[..name.len() - labels.len()];
+                if prefix.len() > 1 && prefix.ends_with(b".") {
+                    let prefix = &prefix[..prefix.len() - 1];
+                    let label = prefix.rsplit(|x| *x == b'.').next().unwrap_or_default();
+                    &name[prefix.len() - label.len()..]
+                } else {
+                    labels
+                }
+            }
+            Kind::Implicit => &labels[labels.len()..],
+        };
+        Rule {
+            bytes,
+            suffix,
+            kind,
+        }
+    }
+
+    /// The labels of the rule as bytes, without any `*.` or `!` prefix
+    ///
+    /// For wildcard rules these are the labels to the right of the `*`.
+    /// The implicit `*` rule has no labels.
+    #[inline]
+    #[must_use]
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// Whether this is a normal, wildcard, exception or the implicit rule
+    #[inline]
+    #[must_use]
+    pub const fn kind(&self) -> Kind {
+        self.kind
+    }
+
+    /// The public suffix this rule produced
+    #[inline]
+    #[must_use]
+    pub const fn suffix(&self) -> Suffix<'a> {
+        self.suffix
+    }
+
+    /// The section of the list this rule is in, if any
+    #[inline]
+    #[must_use]
+    pub const fn typ(&self) -> Option<Type> {
+        self.suffix.typ
+    }
+}
+
+impl fmt::Display for Rule<'_> {
+    /// Formats the rule the way it is written in the public suffix list
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            Kind::Normal => {}
+            Kind::Wildcard if self.bytes.is_empty() => return f.write_str("*"),
+            Kind::Wildcard => f.write_str("*.")?,
+            Kind::Exception => f.write_str("!")?,
+            Kind::Implicit => return f.write_str("*"),
+        }
+        write_lossy(f, self.bytes)
+    }
+}
+
+/// What to do with a cookie based on its `Domain` attribute
+///
+/// This is returned by [`List::cookie_domain`].
+///
+/// [`List::cookie_domain`]: trait.List.html#method.cookie_domain
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum CookieDomain<'a> {
+    /// Store the cookie for the request host only
+    ///
+    /// The attribute is empty or names the request host, which is itself a
+    /// public suffix.
+    HostOnly,
+    /// Store the cookie for this domain and its subdomains
+    Domain(&'a [u8]),
+    /// Ignore the cookie because the attribute is a public suffix
+    PublicSuffix,
+    /// Ignore the cookie because the request host is not in the attribute's domain
+    Mismatch,
+}
+
+impl CookieDomain<'_> {
+    /// Whether the cookie should be stored
+    #[inline]
+    #[must_use]
+    pub fn is_accepted(&self) -> bool {
+        match self {
+            CookieDomain::HostOnly | CookieDomain::Domain(_) => true,
+            CookieDomain::PublicSuffix | CookieDomain::Mismatch => false,
+        }
+    }
+}
+
+/// Why a domain name was rejected by the checked lookups
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum NameError {
+    /// The name is empty or only a `.`
+    Empty,
+    /// The name has an empty label, like `a..com` or `.com`
+    EmptyLabel,
+    /// A label is longer than 63 bytes
+    LabelTooLong,
+    /// The name is longer than 253 bytes, not counting a trailing `.`
+    NameTooLong,
+    /// The name contains an ASCII byte other than a letter, digit, `-` or `_`
+    InvalidByte(u8),
+    /// The name contains non-ASCII bytes that are not valid UTF-8
+    InvalidUtf8,
+}
+
+impl fmt::Display for NameError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NameError::Empty => f.write_str("domain name is empty"),
+            NameError::EmptyLabel => f.write_str("domain name has an empty label"),
+            NameError::LabelTooLong => {
+                write!(f, "label is longer than {} bytes", MAX_LABEL_LEN)
+            }
+            NameError::NameTooLong => {
+                write!(f, "domain name is longer than {} bytes", MAX_NAME_LEN)
+            }
+            NameError::InvalidByte(byte) => {
+                write!(f, "invalid byte {:?} in domain name", char::from(*byte))
+            }
+            NameError::InvalidUtf8 => f.write_str("domain name is not valid UTF-8"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NameError {}
+
+/// The longest label allowed by RFC 1035, in bytes
+const MAX_LABEL_LEN: usize = 63;
+
+/// The longest name allowed by RFC 1035 in its text form, in bytes
+const MAX_NAME_LEN: usize = 253;
+
+/// Checks that `name` is a valid domain name, which may end with a `.`
+pub(crate) fn validate_name(name: &[u8]) -> Result<(), NameError> {
+    let name = strip_dot(name);
+    if name.is_empty() {
+        return Err(NameError::Empty);
+    }
+    if name.len() > MAX_NAME_LEN {
+        return Err(NameError::NameTooLong);
+    }
+    for label in name.split(is_dot) {
+        if label.is_empty() {
+            return Err(NameError::EmptyLabel);
+        }
+        if label.len() > MAX_LABEL_LEN {
+            return Err(NameError::LabelTooLong);
+        }
+        let invalid = label.iter().find(|byte| {
+            byte.is_ascii() && !(byte.is_ascii_alphanumeric() || b"-_".contains(byte))
+        });
+        if let Some(byte) = invalid {
+            return Err(NameError::InvalidByte(*byte));
+        }
+    }
+    if !name.is_ascii() && core::str::from_utf8(name).is_err() {
+        return Err(NameError::InvalidUtf8);
+    }
+    Ok(())
+}
+
+/// The most labels a domain name can have
+const MAX_LABELS: usize = 128;
+
+/// How many names the batch lookups process at a time
+const BATCH_LEN: usize = 64;
+
+type ForwardLabels<'a> = Split<'a, u8, fn(&u8) -> bool>;
+
+type ReversedLabels<'a> = RSplit<'a, u8, fn(&u8) -> bool>;
+
+#[inline]
+fn reversed_labels(name: &[u8]) -> (ReversedLabels<'_>, bool) {
+    let mut labels = name.rsplit(is_dot as fn(&u8) -> bool);
+    let fqdn = if name.ends_with(b".") {
+        labels.next();
+        true
+    } else {
+        false
+    };
+    (labels, fqdn)
+}
+
+#[inline]
+fn suffix_from_info(name: &[u8], fqdn: bool, info: Info) -> Option<Suffix<'_>> {
+    let Info { mut len, typ } = info;
+    if fqdn {
+        len += 1;
+    }
+    if len == 0 {
+        return None;
+    }
+    let offset = name.len().checked_sub(len)?;
+    let bytes = name.get(offset..)?;
+    Some(Suffix { bytes, fqdn, typ })
+}
+
+/// Replaces a private match with the longest match outside the private section
+///
+/// Rules with fewer labels than the private one are found by looking up
+/// only as many labels as that shorter rule can match.
+fn icann_fallback<L: List + ?Sized>(list: &L, name: &[u8], mut info: Info) -> Info {
+    let trimmed = strip_dot(name);
+    while info.typ == Some(Type::Private) {
+        let suffix = match trimmed.len().checked_sub(info.len) {
+            Some(offset) => &trimmed[offset..],
+            None => break,
+        };
+        let count = suffix.iter().filter(|x| is_dot(x)).count() + 1;
+        if count == 1 {
+            let len = reversed_labels(name).0.next().map_or(0, <[u8]>::len);
+            return Info { len, typ: None };
+        }
+        info = list.find(reversed_labels(name).0.take(count - 1));
+    }
+    info
+}
+
+#[inline]
+fn domain_from_suffix<'a>(name: &'a [u8], suffix: Suffix<'a>) -> Option<Domain<'a>> {
+    let name_len = name.len();
+    let suffix_len = suffix.bytes.len();
+    if name_len < suffix_len + 2 {
+        return None;
+    }
+    let offset = name_len - (1 + suffix_len);
+    let subdomain = name.get(..offset)?;
+    let root_label = subdomain.rsplitn(2, |x| *x == b'.').next()?;
+    let registrable_len = root_label.len() + 1 + suffix_len;
+    let offset = name_len - registrable_len;
+    let bytes = name.get(offset..)?;
+    Some(Domain { bytes, suffix })
+}
+
+/// Writes `bytes` as UTF-8, replacing invalid sequences with `U+FFFD`
+fn write_lossy(f: &mut fmt::Formatter<'_>, mut bytes: &[u8]) -> fmt::Result {
+    loop {
+        match core::str::from_utf8(bytes) {
+            Ok(text) => return f.write_str(text),
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                f.write_str(core::str::from_utf8(valid).unwrap_or_default())?;
+                f.write_str("\u{FFFD}")?;
+                let invalid = error.error_len().unwrap_or(rest.len());
+                bytes = &rest[invalid..];
+            }
+        }
+    }
+}
+
+/// Whether `host` domain-matches `domain` as defined by RFC 6265 section 5.1.3
+fn domain_match(host: &[u8], domain: &[u8]) -> bool {
+    if host.eq_ignore_ascii_case(domain) {
+        return true;
+    }
+    host.len() > domain.len()
+        && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+        && host[host.len() - domain.len() - 1] == b'.'
+        && !host::is_ip(host)
+}
+
+#[inline]
+fn is_dot(byte: &u8) -> bool {
+    *byte == b'.'
+}
+
+#[inline]
+fn strip_dot(bytes: &[u8]) -> &[u8] {
+    if bytes.ends_with(b".") {
+        &bytes[..bytes.len() - 1]
+    } else {
+        bytes
+    }
+}
+
+/// Test lists shared by the test modules of the crate
+#[cfg(test)]
+pub(crate) mod fixture {
+    use super::{Info, List};
+
+    /// Treats the last label as the suffix
+    pub(crate) struct Tld;
+
+    impl List for Tld {
+        fn find<'a, T>(&self, mut labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            let len = labels.next().map(<[u8]>::len).unwrap_or_default();
+            Info { len, typ: None }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        CookieDomain, DefaultRule, Info, Kind, List as Psl, Match, NameError, Options, Type,
+    };
+
+    struct List;
+
+    impl Psl for List {
+        fn find<'a, T>(&self, mut labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            match labels.next() {
+                Some(label) => Info {
                     len: label.len(),
                     typ: None,
                 },
@@ -306,6 +1687,90 @@ mod test {
         }
     }
 
+    /// Knows about the `*.ck` and `!www.ck` rules only
+    struct Ck;
+
+    impl Psl for Ck {
+        fn find<'a, T>(&self, labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            self.find_match(labels).info
+        }
+
+        fn find_match<'a, T>(&self, mut labels: T) -> Match
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            let tld = match labels.next() {
+                Some(label) => label,
+                None => {
+                    return Match {
+                        info: Info { len: 0, typ: None },
+                        kind: Kind::Implicit,
+                    }
+                }
+            };
+            let implicit = Match {
+                info: Info {
+                    len: tld.len(),
+                    typ: None,
+                },
+                kind: Kind::Implicit,
+            };
+            if tld != b"ck" {
+                return implicit;
+            }
+            match labels.next() {
+                Some(b"www") => Match {
+                    info: Info {
+                        len: tld.len(),
+                        typ: Some(Type::Icann),
+                    },
+                    kind: Kind::Exception,
+                },
+                Some(label) => Match {
+                    info: Info {
+                        len: label.len() + 1 + tld.len(),
+                        typ: Some(Type::Icann),
+                    },
+                    kind: Kind::Wildcard,
+                },
+                None => implicit,
+            }
+        }
+    }
+
+    /// Knows about `com` and the private `blogspot.com` and `private`
+    struct Private;
+
+    impl Psl for Private {
+        fn find<'a, T>(&self, mut labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            match (labels.next(), labels.next()) {
+                (Some(b"com"), Some(b"blogspot")) => Info {
+                    len: 12,
+                    typ: Some(Type::Private),
+                },
+                (Some(b"com"), _) => Info {
+                    len: 3,
+                    typ: Some(Type::Icann),
+                },
+                (Some(b"private"), _) => Info {
+                    len: 7,
+                    typ: Some(Type::Private),
+                },
+                (Some(label), _) => Info {
+                    len: label.len(),
+                    typ: None,
+                },
+                (None, _) => Info { len: 0, typ: None },
+            }
+        }
+    }
+
     #[test]
     fn www_example_com() {
         let domain = List.domain(b"www.example.com").expect("domain name");
@@ -392,6 +1857,123 @@ mod test {
         assert_eq!(suffix, "com");
     }
 
+    #[test]
+    fn cursor() {
+        let mut cursor = Ck.cursor();
+        assert_eq!(cursor.info(), Info { len: 0, typ: None });
+        let info = cursor.push(b"ck").expect("info");
+        assert_eq!(info, Info { len: 2, typ: None });
+
+        cursor.push(b"www");
+        assert_eq!(cursor.current().kind, Kind::Exception);
+        assert_eq!(cursor.info().len, 2);
+        assert_eq!(cursor.pop(), Some(&b"www"[..]));
+
+        cursor.push(b"foo");
+        assert_eq!(cursor.current().kind, Kind::Wildcard);
+        assert_eq!(cursor.info().len, 6);
+        assert_eq!(cursor.labels(), &[&b"ck"[..], b"foo"]);
+
+        assert_eq!(cursor.pop(), Some(&b"foo"[..]));
+        assert_eq!(cursor.pop(), Some(&b"ck"[..]));
+        assert_eq!(cursor.pop(), None);
+
+        let mut cursor = List.cursor();
+        for _ in 0..128 {
+            assert!(cursor.push(b"a").is_some());
+        }
+        assert_eq!(cursor.push(b"a"), None);
+    }
+
+    #[test]
+    fn cookie_domain() {
+        let host = b"www.example.com";
+        let verdict = Private.cookie_domain(host, b"example.com");
+        assert_eq!(verdict, CookieDomain::Domain(b"example.com"));
+        assert!(verdict.is_accepted());
+        let verdict = Private.cookie_domain(host, b".example.com");
+        assert_eq!(verdict, CookieDomain::Domain(b"example.com"));
+        let verdict = Private.cookie_domain(b"www.Example.COM", b"EXAMPLE.com");
+        assert_eq!(verdict, CookieDomain::Domain(b"EXAMPLE.com"));
+        let verdict = Private.cookie_domain(host, b"www.example.com");
+        assert_eq!(verdict, CookieDomain::Domain(b"www.example.com"));
+        let verdict = Private.cookie_domain(b"www.example.com.", b"example.com");
+        assert_eq!(verdict, CookieDomain::Domain(b"example.com"));
+        assert_eq!(Private.cookie_domain(host, b""), CookieDomain::HostOnly);
+        assert_eq!(Private.cookie_domain(host, b"."), CookieDomain::HostOnly);
+
+        let verdict = Private.cookie_domain(host, b"com");
+        assert_eq!(verdict, CookieDomain::PublicSuffix);
+        assert!(!verdict.is_accepted());
+        let verdict = Private.cookie_domain(b"foo.blogspot.com", b"blogspot.com");
+        assert_eq!(verdict, CookieDomain::PublicSuffix);
+        let verdict = Private.cookie_domain(b"blogspot.com", b".blogspot.com");
+        assert_eq!(verdict, CookieDomain::HostOnly);
+        let verdict = Private.cookie_domain(b"blogspot.com.", b"blogspot.com");
+        assert_eq!(verdict, CookieDomain::HostOnly);
+
+        let verdict = Private.cookie_domain(host, b"other.com");
+        assert_eq!(verdict, CookieDomain::Mismatch);
+        let verdict = Private.cookie_domain(host, b"ample.com");
+        assert_eq!(verdict, CookieDomain::Mismatch);
+        let verdict = Private.cookie_domain(b"example.com", b"www.example.com");
+        assert_eq!(verdict, CookieDomain::Mismatch);
+        let verdict = Private.cookie_domain(b"192.0.2.1", b"0.2.1");
+        assert_eq!(verdict, CookieDomain::Mismatch);
+    }
+
+    #[test]
+    fn all_suffixes() {
+        let suffixes = Private.all_suffixes(b"a.b.foo.blogspot.com.");
+        assert!(suffixes
+            .clone()
+            .eq(["com.", "blogspot.com."].iter().copied()));
+        let types = suffixes.map(|suffix| suffix.typ());
+        assert!(types.eq([Some(Type::Icann), Some(Type::Private)].iter().copied()));
+
+        assert_eq!(List.all_suffixes(b"www.example.com").count(), 0);
+        assert_eq!(Ck.all_suffixes(b"www.ck").count(), 0);
+        let suffixes = Ck.all_suffixes(b"www.foo.ck");
+        assert!(suffixes.eq(["foo.ck"].iter().copied()));
+    }
+
+    #[test]
+    fn str_lookups() {
+        extern crate alloc;
+        use alloc::string::ToString;
+
+        let domain = List.domain_str("www.食狮.中国.").expect("domain name");
+        assert_eq!(domain.as_str(), "食狮.中国.");
+        assert_eq!(domain.trim().as_str(), "食狮.中国");
+        assert_eq!(domain.suffix().as_str(), "中国.");
+        assert_eq!(domain.suffix().trim().as_str(), "中国");
+        assert_eq!(domain.to_string(), "食狮.中国.");
+        assert_eq!(domain, "食狮.中国");
+
+        let suffix = List.suffix_str("example.com").expect("suffix");
+        assert_eq!(suffix.as_str(), "com");
+        assert_eq!(
+            suffix.as_suffix(),
+            List.suffix(b"example.com").expect("suffix")
+        );
+        assert_eq!(List.domain_str("com"), None);
+    }
+
+    #[test]
+    fn display() {
+        extern crate alloc;
+        use alloc::string::ToString;
+
+        let domain = List.domain(b"www.example.com").expect("domain name");
+        assert_eq!(domain.as_str(), Some("example.com"));
+        assert_eq!(domain.to_string(), "example.com");
+        assert_eq!(domain.suffix().to_string(), "com");
+
+        let suffix = List.suffix(b"example.c\xffm").expect("suffix");
+        assert_eq!(suffix.as_str(), None);
+        assert_eq!(suffix.to_string(), "c\u{FFFD}m");
+    }
+
     #[test]
     fn root() {
         let domain = List.domain(b".");
@@ -401,6 +1983,172 @@ mod test {
         assert_eq!(suffix, ".");
     }
 
+    #[test]
+    fn name_parts() {
+        let name = List.name(b"www.api.example.com.").expect("domain name");
+        assert_eq!(name.as_bytes(), b"www.api.example.com.");
+        assert_eq!(name.subdomain(), Some(&b"www.api"[..]));
+        assert_eq!(name.root(), Some(&b"example"[..]));
+        assert_eq!(name.domain().expect("domain name"), "example.com.");
+        assert_eq!(name.suffix(), "com.");
+
+        let name = List.name(b"example.com").expect("domain name");
+        assert_eq!(name.subdomain(), None);
+        assert_eq!(name.root(), Some(&b"example"[..]));
+
+        let name = List.name(b"com").expect("domain name");
+        assert_eq!(name.domain(), None);
+        assert_eq!(name.root(), None);
+        assert_eq!(name.subdomain(), None);
+    }
+
+    #[test]
+    fn labels() {
+        let domain = List.domain(b"www.example.com.").expect("domain name");
+        let mut labels = domain.labels();
+        assert_eq!(labels.next(), Some(&b"example"[..]));
+        assert_eq!(labels.next(), Some(&b"com"[..]));
+        assert_eq!(labels.next(), None);
+
+        let mut labels = domain.labels().rev();
+        assert_eq!(labels.next(), Some(&b"com"[..]));
+        assert_eq!(labels.next(), Some(&b"example"[..]));
+        assert_eq!(labels.next(), None);
+
+        let suffix = List.suffix(b".").expect("public suffix");
+        assert_eq!(suffix.labels().next(), None);
+    }
+
+    #[test]
+    fn same_site() {
+        assert!(List.same_site(b"www.example.com", b"example.com."));
+        assert!(List.same_site(b"a.example.com", b"b.example.com"));
+        assert!(!List.same_site(b"example.com", b"example.org"));
+        assert!(List.same_site(b"com.", b"com"));
+        assert!(!List.same_site(b"com", b"example.com"));
+    }
+
+    #[test]
+    fn same_registrable_domain() {
+        assert!(List.same_registrable_domain(b"www.example.com.", b"example.com"));
+        assert!(!List.same_registrable_domain(b"example.com", b"example.org"));
+        assert!(!List.same_registrable_domain(b"com", b"com"));
+        assert!(Ck.same_site(b"www.ck", b"www.www.ck"));
+        assert!(!Ck.same_site(b"a.test.ck", b"b.test.ck"));
+    }
+
+    #[test]
+    fn same_site_with() {
+        let icann = Options::new().private(false);
+        let (a, b) = (b"a.blogspot.com", b"b.blogspot.com.");
+        assert!(!Private.same_site(a, b));
+        assert!(!Private.same_site_with(a, b, Options::new()));
+        assert!(Private.same_site_with(a, b, icann));
+        assert!(!Private.same_registrable_domain(a, b));
+        assert!(Private.same_registrable_domain_with(a, b, icann));
+        assert!(!Private.same_registrable_domain_with(b"blogspot.com", b"com", icann));
+        assert!(Private.same_site_with(b"blogspot.com", b"blogspot.com.", Options::new()));
+
+        let unregistrable = Options::new().default_rule(DefaultRule::Reject);
+        assert!(Private.same_site(b"a.example.test", b"b.example.test"));
+        assert!(!Private.same_site_with(b"a.example.test", b"b.example.test", unregistrable));
+    }
+
+    #[test]
+    fn icann_only() {
+        let options = Options::new().private(false);
+
+        let domain = Private.domain(b"foo.blogspot.com").expect("domain name");
+        assert_eq!(domain, "foo.blogspot.com");
+        assert_eq!(domain.suffix().typ(), Some(Type::Private));
+
+        let domain = Private
+            .domain_with(b"foo.blogspot.com.", options)
+            .expect("domain name");
+        assert_eq!(domain, "blogspot.com.");
+        assert_eq!(domain.suffix(), "com.");
+        assert_eq!(domain.suffix().typ(), Some(Type::Icann));
+
+        let suffix = Private
+            .suffix_with(b"example.private", options)
+            .expect("public suffix");
+        assert_eq!(suffix, "private");
+        assert_eq!(suffix.typ(), None);
+
+        let domain = Private
+            .domain_with(b"www.example.com", options)
+            .expect("domain name");
+        assert_eq!(domain, "example.com");
+    }
+
+    #[test]
+    fn default_rule() {
+        let reject = Options::new().default_rule(DefaultRule::Reject);
+        assert_eq!(Private.suffix_with(b"printer.corp", reject), None);
+        assert_eq!(Private.domain_with(b"printer.corp", reject), None);
+        let domain = Private
+            .domain_with(b"www.example.com", reject)
+            .expect("domain name");
+        assert_eq!(domain, "example.com");
+
+        let unregistrable = Options::new().default_rule(DefaultRule::Unregistrable);
+        let suffix = Private
+            .suffix_with(b"printer.corp.", unregistrable)
+            .expect("public suffix");
+        assert_eq!(suffix.as_bytes(), b"printer.corp.");
+        assert_eq!(suffix.typ(), None);
+        assert_eq!(Private.domain_with(b"printer.corp", unregistrable), None);
+
+        let icann_only = reject.private(false);
+        assert_eq!(Private.suffix_with(b"example.private", icann_only), None);
+    }
+
+    #[test]
+    fn batch_lookups() {
+        let names: [&[u8]; 5] = [
+            b"www.example.com",
+            b"example.com.",
+            b"com",
+            b".example.com",
+            b"",
+        ];
+        let mut domains = [None; 5];
+        List.domains(&names, &mut domains);
+        let mut suffixes = [None; 5];
+        List.suffixes(&names, &mut suffixes);
+        for ((name, domain), suffix) in names.iter().zip(&domains).zip(&suffixes) {
+            assert_eq!(*domain, List.domain(name));
+            assert_eq!(*suffix, List.suffix(name));
+        }
+    }
+
+    #[test]
+    fn large_batch() {
+        let names = [&b"www.example.com"[..]; 150];
+        let mut domains = [None; 150];
+        List.domains(&names, &mut domains);
+        assert!(domains
+            .iter()
+            .all(|domain| *domain == List.domain(names[0])));
+    }
+
+    #[test]
+    #[should_panic]
+    fn batch_length_mismatch() {
+        let mut domains = [None; 1];
+        List.domains(&[b"example.com", b"example.org"], &mut domains);
+    }
+
+    #[test]
+    fn leading_dot() {
+        let suffix = List.suffix(b".example.com").expect("public suffix");
+        assert_eq!(suffix, "com");
+
+        let domain = List.domain(b".example.com").expect("domain name");
+        assert_eq!(domain, "example.com");
+        assert_eq!(domain.suffix(), suffix);
+    }
+
     #[test]
     fn empty_string() {
         let domain = List.domain(b"");
@@ -410,6 +2158,129 @@ mod test {
         assert_eq!(suffix, None);
     }
 
+    #[test]
+    fn checked_lookups() {
+        let domain = List.try_domain(b"www.example.com.").expect("valid name");
+        assert_eq!(domain.expect("domain name"), "example.com.");
+        assert_eq!(List.try_domain(b"com"), Ok(None));
+        let suffix = List.try_suffix(b"_dmarc.example.com").expect("valid name");
+        assert_eq!(suffix.expect("suffix"), "com");
+        assert!(List.try_suffix("食狮.中国".as_bytes()).is_ok());
+
+        assert_eq!(List.try_suffix(b""), Err(NameError::Empty));
+        assert_eq!(List.try_suffix(b"."), Err(NameError::Empty));
+        assert_eq!(List.try_suffix(b"a..com"), Err(NameError::EmptyLabel));
+        assert_eq!(List.try_domain(b".example.com"), Err(NameError::EmptyLabel));
+        assert_eq!(
+            List.try_suffix(b"example.com.."),
+            Err(NameError::EmptyLabel)
+        );
+        assert_eq!(
+            List.try_suffix(b"exa mple.com"),
+            Err(NameError::InvalidByte(b' '))
+        );
+        assert_eq!(
+            List.try_suffix(b"example.com/"),
+            Err(NameError::InvalidByte(b'/'))
+        );
+        assert_eq!(List.try_suffix(b"\xff.com"), Err(NameError::InvalidUtf8));
+
+        let mut name = [b'a'; 256];
+        name[63] = b'.';
+        assert!(List.try_suffix(&name[..127]).is_ok());
+        name[63] = b'a';
+        assert_eq!(List.try_suffix(&name[..127]), Err(NameError::LabelTooLong));
+        for index in (63..256).step_by(64) {
+            name[index] = b'.';
+        }
+        assert!(List.try_suffix(&name[..253]).is_ok());
+        let mut fqdn = [b'.'; 254];
+        fqdn[..253].copy_from_slice(&name[..253]);
+        assert!(List.try_suffix(&fqdn).is_ok());
+        assert_eq!(List.try_suffix(&name[..254]), Err(NameError::NameTooLong));
+
+        struct Empty;
+
+        impl super::List for Empty {
+            fn find<'a, T>(&self, _labels: T) -> Info
+            where
+                T: Iterator<Item = &'a [u8]>,
+            {
+                Info { len: 0, typ: None }
+            }
+        }
+
+        assert_eq!(Empty.try_suffix(b"example.com"), Ok(None));
+        assert_eq!(Empty.try_domain(b"example.com"), Ok(None));
+        assert_eq!(Empty.try_suffix(b""), Err(NameError::Empty));
+    }
+
+    #[test]
+    fn implicit_rule() {
+        extern crate alloc;
+        use alloc::string::ToString;
+
+        let rule = List.rule(b"www.example.com").expect("rule");
+        assert_eq!(rule.kind(), Kind::Implicit);
+        assert_eq!(rule.suffix(), "com");
+        assert_eq!(rule.as_bytes(), b"");
+        assert_eq!(rule.to_string(), "*");
+    }
+
+    #[test]
+    fn wildcard_rule() {
+        extern crate alloc;
+        use alloc::string::ToString;
+
+        let rule = Ck.rule(b"a.b.test.ck").expect("rule");
+        assert_eq!(rule.kind(), Kind::Wildcard);
+        assert_eq!(rule.typ(), Some(Type::Icann));
+        assert_eq!(rule.suffix(), "test.ck");
+        assert_eq!(rule.to_string(), "*.ck");
+    }
+
+    #[test]
+    fn exception_rule() {
+        extern crate alloc;
+        use alloc::string::ToString;
+
+        let rule = Ck.rule(b"www.www.ck.").expect("rule");
+        assert_eq!(rule.kind(), Kind::Exception);
+        assert_eq!(rule.suffix(), "ck.");
+        assert_eq!(rule.as_bytes(), b"www.ck");
+        assert_eq!(rule.to_string(), "!www.ck");
+        assert_eq!(Ck.domain(b"www.www.ck.").expect("domain name"), "www.ck");
+    }
+
+    #[test]
+    fn invalid_utf8_rule() {
+        extern crate alloc;
+        use alloc::string::ToString;
+
+        /// Has a rule for every name it is asked about
+        struct Exact;
+
+        impl Psl for Exact {
+            fn find<'a, T>(&self, labels: T) -> Info
+            where
+                T: Iterator<Item = &'a [u8]>,
+            {
+                let len = labels.fold(0, |len, label| match len {
+                    0 => label.len(),
+                    len => len + 1 + label.len(),
+                });
+                Info {
+                    len,
+                    typ: Some(Type::Icann),
+                }
+            }
+        }
+
+        let rule = Exact.rule(b"example.\xffx").expect("rule");
+        assert_eq!(rule.kind(), Kind::Normal);
+        assert_eq!(rule.to_string(), "example.\u{FFFD}x");
+    }
+
     #[test]
     #[allow(dead_code)]
     fn accessors_borrow_correctly() {
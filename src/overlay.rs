@@ -0,0 +1,318 @@
+//! Combining several lists into one
+//!
+//! [`Union`] looks names up in two lists and picks the prevailing match,
+//! which makes it possible to add your own suffixes on top of the official
+//! list. [`Shadow`] removes rules from a list. Both implement `List` so they
+//! can be nested to combine more than two lists.
+//!
+//! ```rust
+//! # use psl_types::{Info, List, Type};
+//! # struct Rules(&'static [&'static str]);
+//! # impl List for Rules {
+//! #     fn find<'a, T: Iterator<Item = &'a [u8]>>(&self, labels: T) -> Info {
+//! #         let mut name = Vec::new();
+//! #         let mut found = Info { len: 0, typ: None };
+//! #         for label in labels {
+//! #             if !name.is_empty() {
+//! #                 name.insert(0, b'.');
+//! #             }
+//! #             name.splice(..0, label.iter().copied());
+//! #             if self.0.iter().any(|rule| rule.as_bytes() == &name[..]) {
+//! #                 found = Info { len: name.len(), typ: Some(Type::Icann) };
+//! #             } else if found.len == 0 {
+//! #                 found.len = name.len();
+//! #             }
+//! #         }
+//! #         found
+//! #     }
+//! # }
+//! # let official = Rules(&["com", "blogspot.com"]);
+//! # let removed = Rules(&["blogspot.com"]);
+//! # let internal = Rules(&["corp"]);
+//! use psl_types::overlay::{Shadow, Union};
+//!
+//! // `official` minus the rules in `removed`, plus the rules in `internal`
+//! let list = Union::new(Shadow::new(official, removed), internal);
+//! assert_eq!(list.suffix(b"foo.blogspot.com").unwrap(), "com");
+//! assert!(list.suffix(b"printer.corp").unwrap().is_known());
+//! ```
+
+use crate::{Info, Kind, List, Match, Metadata, Type, MAX_LABELS};
+
+/// Looks up names in two lists, returning the prevailing match
+///
+/// Like with the rules in a single list, an exception match takes
+/// precedence over any other match. Otherwise the longest match wins. When
+/// both lists find a suffix of the same length, a known suffix wins over
+/// the implicit `*` rule and an ICANN suffix wins over a private one. Any
+/// remaining tie goes to the second (overlay) list.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Union<A, B> {
+    base: A,
+    overlay: B,
+}
+
+impl<A, B> Union<A, B> {
+    /// Combines `base` with `overlay`
+    #[inline]
+    #[must_use]
+    pub const fn new(base: A, overlay: B) -> Self {
+        Union { base, overlay }
+    }
+}
+
+impl<A: List, B: List> List for Union<A, B> {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.find_match(labels).info
+    }
+
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let labels = Labels::collect(labels);
+        let base = self.base.find_match(labels.iter());
+        let overlay = self.overlay.find_match(labels.iter());
+        if prevails(base, overlay) {
+            base
+        } else {
+            overlay
+        }
+    }
+
+    fn find_metadata<'a, T>(&self, labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let labels = Labels::collect(labels);
+        let base = self.base.find_match(labels.iter());
+        let overlay = self.overlay.find_match(labels.iter());
+        if prevails(base, overlay) {
+            self.base.find_metadata(labels.iter())
+        } else {
+            self.overlay.find_metadata(labels.iter())
+        }
+    }
+}
+
+/// Whether the match from the base list wins over the one from the overlay
+fn prevails(base: Match, overlay: Match) -> bool {
+    match (base.kind, overlay.kind) {
+        (Kind::Exception, kind) if kind != Kind::Exception => true,
+        (kind, Kind::Exception) if kind != Kind::Exception => false,
+        _ => precedence(base) > precedence(overlay),
+    }
+}
+
+fn precedence(found: Match) -> (usize, bool, bool) {
+    let Info { len, typ } = found.info;
+    (len, typ.is_some(), typ == Some(Type::Icann))
+}
+
+/// Removes the rules matched by another list from a list
+///
+/// A match from `list` is dropped if `shadow` finds a known suffix of the
+/// same length using the same kind of rule. The longest shorter match from
+/// `list` is used instead. The sections of the rules in `shadow` are ignored.
+///
+/// Exception rules can't be removed this way.
+#[derive(Copy, Clone, Default, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub struct Shadow<L, S> {
+    list: L,
+    shadow: S,
+}
+
+impl<L, S> Shadow<L, S> {
+    /// Removes the rules in `shadow` from `list`
+    #[inline]
+    #[must_use]
+    pub const fn new(list: L, shadow: S) -> Self {
+        Shadow { list, shadow }
+    }
+}
+
+impl<L: List, S: List> List for Shadow<L, S> {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.find_match(labels).info
+    }
+
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let labels = Labels::collect(labels);
+        match self.unshadowed(&labels) {
+            Some((found, _)) => found,
+            None => {
+                let len = labels.iter().next().map_or(0, <[u8]>::len);
+                Match {
+                    info: Info { len, typ: None },
+                    kind: Kind::Implicit,
+                }
+            }
+        }
+    }
+
+    fn find_metadata<'a, T>(&self, labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let labels = Labels::collect(labels);
+        let (_, count) = self.unshadowed(&labels)?;
+        self.list.find_metadata(labels.iter().take(count))
+    }
+}
+
+impl<L: List, S: List> Shadow<L, S> {
+    /// The match from `list` that survives shadowing and how many labels were looked up to find it
+    ///
+    /// Returns `None` if every match was shadowed.
+    fn unshadowed(&self, labels: &Labels<'_>) -> Option<(Match, usize)> {
+        let mut count = labels.len;
+        loop {
+            let found = self.list.find_match(labels.iter().take(count));
+            if found.kind == Kind::Exception || found.info.typ.is_none() {
+                return Some((found, count));
+            }
+            let shadowed = self.shadow.find_match(labels.iter().take(count));
+            if shadowed.info.typ.is_none()
+                || shadowed.info.len != found.info.len
+                || shadowed.kind != found.kind
+            {
+                return Some((found, count));
+            }
+            count = labels.count(found.info.len).saturating_sub(1);
+            if count == 0 {
+                return None;
+            }
+        }
+    }
+}
+
+/// Labels buffered on the stack so they can be looked up more than once
+struct Labels<'a> {
+    labels: [&'a [u8]; MAX_LABELS],
+    len: usize,
+}
+
+impl<'a> Labels<'a> {
+    fn collect<T>(iter: T) -> Self
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let mut labels = Labels {
+            labels: [&[]; MAX_LABELS],
+            len: 0,
+        };
+        for (slot, label) in labels.labels.iter_mut().zip(iter) {
+            *slot = label;
+            labels.len += 1;
+        }
+        labels
+    }
+
+    fn iter(&self) -> impl Iterator<Item = &'a [u8]> + '_ {
+        self.labels[..self.len].iter().copied()
+    }
+
+    /// The number of labels a suffix of `len` bytes spans
+    fn count(&self, len: usize) -> usize {
+        let mut total = 0;
+        for (index, label) in self.iter().enumerate() {
+            if total >= len {
+                return index;
+            }
+            if index > 0 {
+                total += 1;
+            }
+            total += label.len();
+        }
+        self.len
+    }
+}
+
+#[cfg(all(test, feature = "alloc"))]
+mod test {
+    use super::{Shadow, Union};
+    use crate::trie::Trie;
+    use crate::{Kind, List, Type};
+
+    const OFFICIAL: &str = "\
+// ===BEGIN ICANN DOMAINS===
+com
+uk
+co.uk
+*.ck
+!www.ck
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+blogspot.com
+// ===END PRIVATE DOMAINS===
+";
+
+    const INTERNAL: &str = "\
+// ===BEGIN PRIVATE DOMAINS===
+internal
+svc.internal
+hosting.example.com
+co.uk
+www.ck
+// ===END PRIVATE DOMAINS===
+";
+
+    #[test]
+    fn union() {
+        let list = Union::new(Trie::parse(OFFICIAL), Trie::parse(INTERNAL));
+
+        let domain = list.domain(b"api.billing.svc.internal").expect("domain");
+        assert_eq!(domain, "billing.svc.internal");
+        assert_eq!(domain.suffix().typ(), Some(Type::Private));
+
+        let domain = list
+            .domain(b"www.customer.hosting.example.com")
+            .expect("domain");
+        assert_eq!(domain, "customer.hosting.example.com");
+
+        let suffix = list.suffix(b"example.co.uk").expect("suffix");
+        assert_eq!(suffix, "co.uk");
+        assert_eq!(suffix.typ(), Some(Type::Icann));
+
+        let rule = list.rule(b"foo.www.ck").expect("rule");
+        assert_eq!(rule.kind(), Kind::Exception);
+        assert_eq!(rule.suffix(), "ck");
+
+        let metadata = list.metadata(b"foo.co.uk").expect("metadata");
+        assert_eq!(metadata.line, Some(4));
+        let metadata = list.metadata(b"foo.blogspot.com").expect("metadata");
+        assert_eq!(metadata.line, Some(9));
+    }
+
+    #[test]
+    fn shadow() {
+        let removed = Trie::parse("// ===BEGIN PRIVATE DOMAINS===\nblogspot.com\n*.ck\n");
+        let list = Shadow::new(Trie::parse(OFFICIAL), removed);
+
+        let suffix = list.suffix(b"foo.blogspot.com").expect("suffix");
+        assert_eq!(suffix, "com");
+        assert_eq!(suffix.typ(), Some(Type::Icann));
+
+        let suffix = list.suffix(b"foo.bar.ck").expect("suffix");
+        assert_eq!(suffix, "ck");
+        assert_eq!(suffix.typ(), None);
+
+        let suffix = list.suffix(b"example.co.uk").expect("suffix");
+        assert_eq!(suffix, "co.uk");
+
+        let metadata = list.metadata(b"foo.blogspot.com").expect("metadata");
+        assert_eq!(metadata.line, Some(2));
+        assert_eq!(list.metadata(b"foo.bar.ck"), None);
+    }
+}
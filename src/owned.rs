@@ -0,0 +1,382 @@
+//! Owned versions of `Domain` and `Suffix`
+//!
+//! These compare, order and hash the same way as the borrowed types so
+//! a trailing `.` does not make a difference. To look up an owned value
+//! in a set or map using a borrowed one, use it as a [`Key`] trait object.
+//!
+//! ```rust
+//! # use psl_types::{Info, List};
+//! # struct Tld;
+//! # impl List for Tld {
+//! #     fn find<'a, T: Iterator<Item = &'a [u8]>>(&self, mut labels: T) -> Info {
+//! #         Info { len: labels.next().map_or(0, <[u8]>::len), typ: None }
+//! #     }
+//! # }
+//! # let list = Tld;
+//! use psl_types::owned::{DomainBuf, Key};
+//! use std::collections::HashSet;
+//!
+//! let mut domains = HashSet::new();
+//! domains.insert(DomainBuf::from(list.domain(b"example.com.").unwrap()));
+//!
+//! let domain = list.domain(b"www.example.com").unwrap();
+//! assert!(domains.contains(&domain as &dyn Key));
+//! ```
+
+use crate::{Domain, Suffix, Type};
+use alloc::vec::Vec;
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+use core::hash::{Hash, Hasher};
+
+/// A domain name or suffix that can be used to look up owned values
+pub trait Key {
+    /// The name as bytes without a trailing `.`
+    fn key(&self) -> &[u8];
+}
+
+impl PartialEq for dyn Key + '_ {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for dyn Key + '_ {}
+
+impl Ord for dyn Key + '_ {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(other.key())
+    }
+}
+
+impl PartialOrd for dyn Key + '_ {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for dyn Key + '_ {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl Key for Suffix<'_> {
+    #[inline]
+    fn key(&self) -> &[u8] {
+        self.trim().bytes
+    }
+}
+
+impl Key for Domain<'_> {
+    #[inline]
+    fn key(&self) -> &[u8] {
+        self.trim().bytes
+    }
+}
+
+/// An owned public suffix
+#[derive(Clone, Debug)]
+pub struct SuffixBuf {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) typ: Option<Type>,
+}
+
+impl SuffixBuf {
+    /// Borrows the suffix
+    #[inline]
+    #[must_use]
+    pub fn as_suffix(&self) -> Suffix<'_> {
+        Suffix::new(&self.bytes, self.typ)
+    }
+
+    /// The suffix as bytes
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Whether or not the suffix is fully qualified (i.e. it ends with a `.`)
+    #[inline]
+    #[must_use]
+    pub fn is_fqdn(&self) -> bool {
+        self.bytes.ends_with(b".")
+    }
+
+    /// Whether this is an `ICANN`, `private` or unknown suffix
+    #[inline]
+    #[must_use]
+    pub const fn typ(&self) -> Option<Type> {
+        self.typ
+    }
+
+    /// Whether or not this is a known suffix (i.e. it is explicitly in the public suffix list)
+    #[inline]
+    #[must_use]
+    pub fn is_known(&self) -> bool {
+        self.typ.is_some()
+    }
+}
+
+impl From<Suffix<'_>> for SuffixBuf {
+    #[inline]
+    fn from(suffix: Suffix<'_>) -> Self {
+        SuffixBuf {
+            bytes: suffix.bytes.into(),
+            typ: suffix.typ,
+        }
+    }
+}
+
+impl Key for SuffixBuf {
+    #[inline]
+    fn key(&self) -> &[u8] {
+        self.as_suffix().trim().bytes
+    }
+}
+
+impl<'a> Borrow<dyn Key + 'a> for SuffixBuf {
+    #[inline]
+    fn borrow(&self) -> &(dyn Key + 'a) {
+        self
+    }
+}
+
+impl PartialEq for SuffixBuf {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for SuffixBuf {}
+
+impl PartialEq<Suffix<'_>> for SuffixBuf {
+    #[inline]
+    fn eq(&self, other: &Suffix<'_>) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl PartialEq<SuffixBuf> for Suffix<'_> {
+    #[inline]
+    fn eq(&self, other: &SuffixBuf) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl PartialEq<&[u8]> for SuffixBuf {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_suffix() == *other
+    }
+}
+
+impl PartialEq<&str> for SuffixBuf {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_suffix() == *other
+    }
+}
+
+impl Ord for SuffixBuf {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(other.key())
+    }
+}
+
+impl PartialOrd for SuffixBuf {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for SuffixBuf {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+/// An owned registrable domain name
+#[derive(Clone, Debug)]
+pub struct DomainBuf {
+    pub(crate) bytes: Vec<u8>,
+    pub(crate) suffix_len: usize,
+    pub(crate) typ: Option<Type>,
+}
+
+impl DomainBuf {
+    /// Borrows the domain name
+    #[inline]
+    #[must_use]
+    pub fn as_domain(&self) -> Domain<'_> {
+        let suffix = &self.bytes[self.bytes.len() - self.suffix_len..];
+        Domain::new(&self.bytes, Suffix::new(suffix, self.typ))
+    }
+
+    /// The domain name as bytes
+    #[inline]
+    #[must_use]
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The public suffix of this domain name
+    #[inline]
+    #[must_use]
+    pub fn suffix(&self) -> Suffix<'_> {
+        self.as_domain().suffix
+    }
+}
+
+impl From<Domain<'_>> for DomainBuf {
+    #[inline]
+    fn from(domain: Domain<'_>) -> Self {
+        DomainBuf {
+            bytes: domain.bytes.into(),
+            suffix_len: domain.suffix.bytes.len(),
+            typ: domain.suffix.typ,
+        }
+    }
+}
+
+impl Key for DomainBuf {
+    #[inline]
+    fn key(&self) -> &[u8] {
+        self.as_domain().trim().bytes
+    }
+}
+
+impl<'a> Borrow<dyn Key + 'a> for DomainBuf {
+    #[inline]
+    fn borrow(&self) -> &(dyn Key + 'a) {
+        self
+    }
+}
+
+impl PartialEq for DomainBuf {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for DomainBuf {}
+
+impl PartialEq<Domain<'_>> for DomainBuf {
+    #[inline]
+    fn eq(&self, other: &Domain<'_>) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl PartialEq<DomainBuf> for Domain<'_> {
+    #[inline]
+    fn eq(&self, other: &DomainBuf) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl PartialEq<&[u8]> for DomainBuf {
+    #[inline]
+    fn eq(&self, other: &&[u8]) -> bool {
+        self.as_domain() == *other
+    }
+}
+
+impl PartialEq<&str> for DomainBuf {
+    #[inline]
+    fn eq(&self, other: &&str) -> bool {
+        self.as_domain() == *other
+    }
+}
+
+impl Ord for DomainBuf {
+    #[inline]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(other.key())
+    }
+}
+
+impl PartialOrd for DomainBuf {
+    #[inline]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Hash for DomainBuf {
+    #[inline]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{DomainBuf, Key, SuffixBuf};
+    use crate::fixture::Tld;
+    use crate::List;
+
+    #[test]
+    fn conversions() {
+        let domain = Tld.domain(b"www.example.com.").expect("domain name");
+        let owned = DomainBuf::from(domain);
+        assert_eq!(owned.as_domain(), domain);
+        assert_eq!(owned, "example.com");
+        assert_eq!(owned.suffix(), "com.");
+        assert!(owned.suffix().is_fqdn());
+
+        let suffix = SuffixBuf::from(domain.suffix());
+        assert_eq!(suffix, "com");
+        assert!(suffix.is_fqdn());
+        assert_eq!(suffix.as_suffix(), domain.suffix());
+    }
+
+    #[test]
+    fn fqdn_comparisons() {
+        let fqdn = DomainBuf::from(Tld.domain(b"example.com.").expect("domain name"));
+        let non_fqdn = Tld.domain(b"example.com").expect("domain name");
+        assert_eq!(fqdn, non_fqdn);
+        assert_eq!(non_fqdn, fqdn);
+        assert_eq!(fqdn, DomainBuf::from(non_fqdn));
+    }
+
+    #[test]
+    fn hashmap_borrowed_lookups() {
+        extern crate std;
+        use std::collections::HashSet;
+
+        let mut domains = HashSet::new();
+        let mut suffixes = HashSet::new();
+
+        let fqdn = Tld.domain(b"example.com.").expect("domain name");
+        domains.insert(DomainBuf::from(fqdn));
+        suffixes.insert(SuffixBuf::from(fqdn.suffix()));
+
+        let non_fqdn = Tld.domain(b"www.example.com").expect("domain name");
+        assert!(domains.contains(&non_fqdn as &dyn Key));
+        assert!(suffixes.contains(&non_fqdn.suffix() as &dyn Key));
+    }
+
+    #[test]
+    fn btreemap_borrowed_lookups() {
+        use alloc::collections::BTreeSet;
+
+        let mut domains = BTreeSet::new();
+        domains.insert(DomainBuf::from(
+            Tld.domain(b"example.com").expect("domain name"),
+        ));
+
+        let fqdn = Tld.domain(b"example.com.").expect("domain name");
+        assert!(domains.contains(&fqdn as &dyn Key));
+    }
+}
@@ -0,0 +1,172 @@
+//! Punycode as specified in [RFC 3492]
+//!
+//! Only the label bodies are converted here; the `xn--` prefix is added and
+//! stripped by the caller.
+//!
+//! [RFC 3492]: https://www.rfc-editor.org/rfc/rfc3492
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+const BASE: u32 = 36;
+const T_MIN: u32 = 1;
+const T_MAX: u32 = 26;
+const SKEW: u32 = 38;
+const DAMP: u32 = 700;
+const INITIAL_BIAS: u32 = 72;
+const INITIAL_N: u32 = 0x80;
+
+/// Decodes a punycode label body, returning `None` if it is malformed
+pub(crate) fn decode(input: &str) -> Option<String> {
+    let (basic, extended) = match input.bytes().rposition(|b| b == b'-') {
+        Some(dash) => (&input[..dash], &input.as_bytes()[dash + 1..]),
+        None => ("", input.as_bytes()),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+    let mut n = INITIAL_N;
+    let mut bias = INITIAL_BIAS;
+    let mut i: u32 = 0;
+    let mut digits = extended.iter();
+    while !digits.as_slice().is_empty() {
+        let old_i = i;
+        let mut weight = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = digit_value(*digits.next()?)?;
+            i = i.checked_add(digit.checked_mul(weight)?)?;
+            let t = threshold(k, bias);
+            if digit < t {
+                break;
+            }
+            weight = weight.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let len = output.len() as u32 + 1;
+        bias = adapt(i - old_i, len, old_i == 0);
+        n = n.checked_add(i / len)?;
+        i %= len;
+        output.insert(i as usize, core::char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+/// Encodes a label as a punycode label body, returning `None` on overflow
+pub(crate) fn encode(input: &str) -> Option<String> {
+    let input: Vec<u32> = input.chars().map(u32::from).collect();
+    let mut output: String = input
+        .iter()
+        .filter(|&&c| c < INITIAL_N)
+        .map(|&c| c as u8 as char)
+        .collect();
+    let basic = output.len() as u32;
+    let mut handled = basic;
+    if basic > 0 {
+        output.push('-');
+    }
+    let mut n = INITIAL_N;
+    let mut delta: u32 = 0;
+    let mut bias = INITIAL_BIAS;
+    while (handled as usize) < input.len() {
+        let m = input.iter().copied().filter(|&c| c >= n).min()?;
+        delta = delta.checked_add((m - n).checked_mul(handled + 1)?)?;
+        n = m;
+        for &c in &input {
+            if c < n {
+                delta = delta.checked_add(1)?;
+            }
+            if c == n {
+                let mut q = delta;
+                let mut k = BASE;
+                loop {
+                    let t = threshold(k, bias);
+                    if q < t {
+                        break;
+                    }
+                    output.push(digit_char(t + (q - t) % (BASE - t)));
+                    q = (q - t) / (BASE - t);
+                    k += BASE;
+                }
+                output.push(digit_char(q));
+                bias = adapt(delta, handled + 1, handled == basic);
+                delta = 0;
+                handled += 1;
+            }
+        }
+        delta += 1;
+        n += 1;
+    }
+    Some(output)
+}
+
+fn threshold(k: u32, bias: u32) -> u32 {
+    if k <= bias {
+        T_MIN
+    } else if k >= bias + T_MAX {
+        T_MAX
+    } else {
+        k - bias
+    }
+}
+
+fn adapt(delta: u32, len: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / DAMP } else { delta / 2 };
+    delta += delta / len;
+    let mut k = 0;
+    while delta > ((BASE - T_MIN) * T_MAX) / 2 {
+        delta /= BASE - T_MIN;
+        k += BASE;
+    }
+    k + (BASE - T_MIN + 1) * delta / (delta + SKEW)
+}
+
+fn digit_value(byte: u8) -> Option<u32> {
+    match byte {
+        b'a'..=b'z' => Some(u32::from(byte - b'a')),
+        b'A'..=b'Z' => Some(u32::from(byte - b'A')),
+        b'0'..=b'9' => Some(u32::from(byte - b'0') + 26),
+        _ => None,
+    }
+}
+
+fn digit_char(digit: u32) -> char {
+    if digit < 26 {
+        (b'a' + digit as u8) as char
+    } else {
+        (b'0' + (digit - 26) as u8) as char
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{decode, encode};
+
+    const SAMPLES: [(&str, &str); 6] = [
+        ("рф", "p1ai"),
+        ("中国", "fiqs8s"),
+        ("公司", "55qx5d"),
+        ("пример", "e1afmkfd"),
+        ("bücher", "bcher-kva"),
+        // RFC 3492 section 7.1 (L)
+        ("3年B組金八先生", "3B-ww4c5e180e575a65lsy2b"),
+    ];
+
+    #[test]
+    fn round_trip() {
+        for &(unicode, ascii) in SAMPLES.iter() {
+            assert_eq!(encode(unicode).as_deref(), Some(ascii), "{}", unicode);
+            assert_eq!(decode(ascii).as_deref(), Some(unicode), "{}", ascii);
+        }
+    }
+
+    #[test]
+    fn malformed() {
+        assert_eq!(decode("a-"), Some("a".into()));
+        assert_eq!(decode("99999999999"), None);
+        assert_eq!(decode("ab_c"), None);
+        assert_eq!(decode("é-abc"), None);
+    }
+}
@@ -0,0 +1,367 @@
+//! Lists that can be replaced while they are being used
+//!
+//! Long running services can keep a [`Reloadable`] list and reload it from
+//! disk whenever a new version of the list is published, without
+//! restarting. Lookups only wait for a reload while the new list is being
+//! swapped in, not while it is being read and parsed.
+
+use crate::trie::Trie;
+use crate::{dat, Domain, Info, List, Match, Options, Step, Suffix, Type};
+use std::fmt;
+use std::fs;
+use std::io;
+use std::mem;
+use std::path::Path;
+use std::sync::{Arc, PoisonError, RwLock};
+
+/// An error found while reloading a list
+#[derive(Debug)]
+pub enum Error {
+    /// The list could not be read
+    Io(io::Error),
+    /// The list could not be parsed
+    Parse(dat::Error),
+    /// The list was parsed but failed the sanity check
+    Rejected,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io(error) => write!(f, "failed to read list: {}", error),
+            Error::Parse(error) => write!(f, "failed to parse list: {}", error),
+            Error::Rejected => f.write_str("list failed the sanity check"),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Parse(error) => Some(error),
+            Error::Rejected => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    #[inline]
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+impl From<dat::Error> for Error {
+    #[inline]
+    fn from(error: dat::Error) -> Self {
+        Error::Parse(error)
+    }
+}
+
+/// A list that can be replaced with a new version while it is in use
+///
+/// Each lookup of a single name takes a snapshot of the current list, so
+/// a lookup that runs while the list is being replaced sees either the old
+/// or the new list but never a mix of both. Calls to [`List::find`] and the
+/// other hooks take a snapshot each, so code that calls them several times
+/// should use [`Reloadable::load`] to stick to one version.
+///
+/// The current list sits behind an `RwLock`, and taking a snapshot takes a
+/// read lock. A lookup waits while a replacement holds the write lock, and
+/// on platforms where a waiting writer blocks new readers it also waits
+/// while a replacement waits for the lock. The write lock is only held to
+/// swap a pointer; reading and parsing a new list and dropping the old one
+/// happen without it.
+///
+/// [`List::find_step`] is forwarded to one snapshot per call, but the
+/// [`Step::node`] it returns belongs to that version of the list. Run
+/// a [`crate::Cursor`] on a snapshot from [`Reloadable::load`] if it may
+/// be used across a reload.
+///
+/// [`List::metadata`] always returns `None` here because the metadata would
+/// borrow from a snapshot that could be dropped by the next reload. Call it
+/// on a snapshot from [`Reloadable::load`] instead.
+///
+/// Before a new list is swapped in it has to pass a sanity check. The
+/// default check makes sure `com` is a known ICANN suffix, which catches
+/// empty and truncated files. Use [`Reloadable::with_check`] for lists
+/// that don't contain `com`.
+pub struct Reloadable<L> {
+    current: RwLock<Arc<L>>,
+    check: fn(&L) -> bool,
+}
+
+impl<L: List> Reloadable<L> {
+    /// Wraps `list`, using the default sanity check for later replacements
+    #[inline]
+    #[must_use]
+    pub fn new(list: L) -> Self {
+        Self::with_check(list, knows_com)
+    }
+}
+
+impl<L> Reloadable<L> {
+    /// Wraps `list`, using `check` to vet later replacements
+    #[inline]
+    #[must_use]
+    pub fn with_check(list: L, check: fn(&L) -> bool) -> Self {
+        Reloadable {
+            current: RwLock::new(Arc::new(list)),
+            check,
+        }
+    }
+
+    /// Returns a snapshot of the current list
+    ///
+    /// Use this to run several lookups against the same version of the list.
+    #[inline]
+    #[must_use]
+    pub fn load(&self) -> Arc<L> {
+        let current = self.current.read().unwrap_or_else(PoisonError::into_inner);
+        Arc::clone(&current)
+    }
+
+    /// Replaces the current list with `list` if it passes the sanity check
+    pub fn replace(&self, list: L) -> Result<(), Error> {
+        if !(self.check)(&list) {
+            return Err(Error::Rejected);
+        }
+        let list = Arc::new(list);
+        let mut current = self.current.write().unwrap_or_else(PoisonError::into_inner);
+        let old = mem::replace(&mut *current, list);
+        drop(current);
+        drop(old);
+        Ok(())
+    }
+}
+
+impl Reloadable<Trie> {
+    /// Reads a list from the `.dat` file at `path`, using the default sanity check
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, Error> {
+        let list = Self::new(Trie::new());
+        list.reload(path)?;
+        Ok(list)
+    }
+
+    /// Replaces the current list with the `.dat` file at `path`
+    ///
+    /// The current list is kept if the file can't be read or parsed or if
+    /// it fails the sanity check.
+    pub fn reload<P: AsRef<Path>>(&self, path: P) -> Result<(), Error> {
+        let src = fs::read_to_string(path)?;
+        self.replace(Trie::try_parse(&src)?)
+    }
+}
+
+impl<L: List> List for Reloadable<L> {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.load().find(labels)
+    }
+
+    #[inline]
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.load().find_match(labels)
+    }
+
+    #[inline]
+    fn find_batch(&self, names: &[&[u8]], out: &mut [Info]) {
+        self.load().find_batch(names, out);
+    }
+
+    #[inline]
+    fn find_all<'a, T>(&self, labels: T, out: &mut [Info]) -> usize
+    where
+        T: Iterator<Item = &'a [u8]> + Clone,
+    {
+        self.load().find_all(labels, out)
+    }
+
+    #[inline]
+    fn find_step(&self, labels: &[&[u8]], prev: Option<Step>) -> Step {
+        self.load().find_step(labels, prev)
+    }
+
+    #[inline]
+    fn suffix_with<'a>(&self, name: &'a [u8], options: Options) -> Option<Suffix<'a>> {
+        self.load().suffix_with(name, options)
+    }
+
+    #[inline]
+    fn domain_with<'a>(&self, name: &'a [u8], options: Options) -> Option<Domain<'a>> {
+        self.load().domain_with(name, options)
+    }
+
+    #[inline]
+    fn same_registrable_domain(&self, a: &[u8], b: &[u8]) -> bool {
+        self.load().same_registrable_domain(a, b)
+    }
+
+    #[inline]
+    fn same_site(&self, a: &[u8], b: &[u8]) -> bool {
+        self.load().same_site(a, b)
+    }
+
+    #[inline]
+    fn same_registrable_domain_with(&self, a: &[u8], b: &[u8], options: Options) -> bool {
+        self.load().same_registrable_domain_with(a, b, options)
+    }
+
+    #[inline]
+    fn same_site_with(&self, a: &[u8], b: &[u8], options: Options) -> bool {
+        self.load().same_site_with(a, b, options)
+    }
+}
+
+impl<L: fmt::Debug> fmt::Debug for Reloadable<L> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Reloadable")
+            .field("current", &self.load())
+            .finish()
+    }
+}
+
+fn knows_com<L: List>(list: &L) -> bool {
+    list.suffix(b"com")
+        .filter(|suffix| suffix.typ() == Some(Type::Icann))
+        .is_some()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Error, Reloadable};
+    use crate::trie::Trie;
+    use crate::{Info, List, Step};
+    use std::format;
+    use std::sync::Arc;
+    use std::vec::Vec;
+    use std::{env, fs, process, thread};
+
+    const OLD: &str = "// ===BEGIN ICANN DOMAINS===\ncom\n";
+    const NEW: &str = "// ===BEGIN ICANN DOMAINS===\ncom\n\
+                       // ===BEGIN PRIVATE DOMAINS===\nblogspot.com\n";
+
+    #[test]
+    fn replace() {
+        let list = Reloadable::new(Trie::parse(OLD));
+        let snapshot = list.load();
+        assert_eq!(list.suffix(b"foo.blogspot.com").expect("suffix"), "com");
+
+        list.replace(Trie::parse(NEW)).expect("new list");
+        let suffix = list.suffix(b"foo.blogspot.com").expect("suffix");
+        assert_eq!(suffix, "blogspot.com");
+        assert_eq!(snapshot.suffix(b"foo.blogspot.com").expect("suffix"), "com");
+
+        assert!(list.metadata(b"foo.blogspot.com").is_none());
+        assert!(list.load().metadata(b"foo.blogspot.com").is_some());
+    }
+
+    #[test]
+    fn find_step() {
+        /// Reports how many labels it was given as the node
+        struct Steps;
+
+        impl List for Steps {
+            fn find<'a, T>(&self, _labels: T) -> Info
+            where
+                T: Iterator<Item = &'a [u8]>,
+            {
+                Info { len: 0, typ: None }
+            }
+
+            fn find_step(&self, labels: &[&[u8]], _prev: Option<Step>) -> Step {
+                Step {
+                    node: Some(labels.len()),
+                    ..Step::default()
+                }
+            }
+        }
+
+        let list = Reloadable::with_check(Steps, |_| true);
+        assert_eq!(list.find_step(&[b"com", b"example"], None).node, Some(2));
+    }
+
+    #[test]
+    fn sanity_check() {
+        let list = Reloadable::new(Trie::parse(NEW));
+        assert!(match list.replace(Trie::new()) {
+            Err(Error::Rejected) => true,
+            _ => false,
+        });
+        assert_eq!(
+            list.suffix(b"foo.blogspot.com").expect("suffix"),
+            "blogspot.com"
+        );
+
+        let list = Reloadable::with_check(Trie::new(), |_| true);
+        assert!(list.replace(Trie::new()).is_ok());
+    }
+
+    #[test]
+    fn reload() {
+        let path = env::temp_dir().join(format!("psl-types-reload-{}.dat", process::id()));
+        fs::write(&path, OLD).expect("write list");
+        let list = Reloadable::open(&path).expect("list");
+
+        fs::write(&path, NEW).expect("write list");
+        list.reload(&path).expect("reload");
+        assert_eq!(
+            list.suffix(b"foo.blogspot.com").expect("suffix"),
+            "blogspot.com"
+        );
+
+        fs::write(&path, "// ===BEGIN ICANN DOMAINS===\n!com\n").expect("write list");
+        assert!(match list.reload(&path) {
+            Err(Error::Parse(_)) => true,
+            _ => false,
+        });
+        fs::write(&path, "").expect("write list");
+        assert!(match list.reload(&path) {
+            Err(Error::Rejected) => true,
+            _ => false,
+        });
+        assert_eq!(
+            list.suffix(b"foo.blogspot.com").expect("suffix"),
+            "blogspot.com"
+        );
+
+        fs::remove_file(&path).expect("remove list");
+        assert!(match list.reload(&path) {
+            Err(Error::Io(_)) => true,
+            _ => false,
+        });
+    }
+
+    #[test]
+    fn concurrent_lookups() {
+        let list = Arc::new(Reloadable::new(Trie::parse(OLD)));
+        let readers: Vec<_> = (0..4)
+            .map(|_| {
+                let list = Arc::clone(&list);
+                thread::spawn(move || {
+                    for _ in 0..1000 {
+                        let suffix = list.suffix(b"foo.blogspot.com").expect("suffix");
+                        assert!(suffix == "com" || suffix == "blogspot.com");
+                    }
+                })
+            })
+            .collect();
+        for src in [NEW, OLD, NEW].iter() {
+            list.replace(Trie::parse(src)).expect("new list");
+        }
+        for reader in readers {
+            reader.join().expect("reader");
+        }
+        assert_eq!(
+            list.suffix(b"foo.blogspot.com").expect("suffix"),
+            "blogspot.com"
+        );
+    }
+}
@@ -0,0 +1,472 @@
+//! Serde support for the suffix and domain types
+//!
+//! Suffixes are serialized as `{ "name": "com.", "typ": "Icann" }` and
+//! domains as `{ "name": "example.com.", "suffix": { .. } }`. Names keep
+//! any trailing `.` so that they round trip unchanged.
+//!
+//! The impls are written by hand rather than derived so that enabling the
+//! `serde` feature doesn't pull in `serde_derive` and its dependencies.
+
+use crate::{Domain, Info, Kind, Match, Suffix, Type};
+use core::fmt;
+use core::marker::PhantomData;
+use serde::de::value::UnitDeserializer;
+use serde::de::{
+    self, Deserialize, Deserializer, EnumAccess, IgnoredAny, IntoDeserializer, MapAccess,
+    SeqAccess, Unexpected, VariantAccess, Visitor,
+};
+use serde::ser::{Error as _, Serialize, SerializeStruct, Serializer};
+
+const TYPES: [&str; 2] = ["Icann", "Private"];
+const KINDS: [&str; 4] = ["Normal", "Wildcard", "Exception", "Implicit"];
+const INFO_FIELDS: [&str; 2] = ["len", "typ"];
+const MATCH_FIELDS: [&str; 2] = ["info", "kind"];
+const SUFFIX_FIELDS: [&str; 2] = ["name", "typ"];
+const DOMAIN_FIELDS: [&str; 2] = ["name", "suffix"];
+
+/// Serializes a struct with two fields
+fn serialize_pair<S, A, B>(
+    serializer: S,
+    name: &'static str,
+    fields: &'static [&'static str; 2],
+    a: &A,
+    b: &B,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    A: Serialize + ?Sized,
+    B: Serialize + ?Sized,
+{
+    let mut state = serializer.serialize_struct(name, 2)?;
+    state.serialize_field(fields[0], a)?;
+    state.serialize_field(fields[1], b)?;
+    state.end()
+}
+
+/// Deserializes a struct with two fields
+///
+/// Like a derived impl this accepts a map or a sequence, ignores unknown
+/// fields and treats a missing `Option` field as `None`.
+fn deserialize_pair<'de, D, A, B>(
+    deserializer: D,
+    name: &'static str,
+    fields: &'static [&'static str; 2],
+) -> Result<(A, B), D::Error>
+where
+    D: Deserializer<'de>,
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+{
+    let visitor = PairVisitor {
+        name,
+        fields,
+        marker: PhantomData,
+    };
+    deserializer.deserialize_struct(name, fields, visitor)
+}
+
+struct PairVisitor<A, B> {
+    name: &'static str,
+    fields: &'static [&'static str; 2],
+    marker: PhantomData<(A, B)>,
+}
+
+impl<'de, A, B> Visitor<'de> for PairVisitor<A, B>
+where
+    A: Deserialize<'de>,
+    B: Deserialize<'de>,
+{
+    type Value = (A, B);
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "struct {}", self.name)
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<(A, B), S::Error> {
+        let a = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+        let b = seq
+            .next_element()?
+            .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+        Ok((a, b))
+    }
+
+    fn visit_map<M: MapAccess<'de>>(self, mut map: M) -> Result<(A, B), M::Error> {
+        let mut a = None;
+        let mut b = None;
+        let names = Identifier {
+            names: self.fields,
+            variants: false,
+        };
+        while let Some(index) = map.next_key_seed(names)? {
+            match index {
+                Some(0) if a.is_none() => a = Some(map.next_value()?),
+                Some(1) if b.is_none() => b = Some(map.next_value()?),
+                Some(index) => return Err(de::Error::duplicate_field(self.fields[index])),
+                None => {
+                    map.next_value::<IgnoredAny>()?;
+                }
+            }
+        }
+        let a = match a {
+            Some(a) => a,
+            None => missing_field(self.fields[0])?,
+        };
+        let b = match b {
+            Some(b) => b,
+            None => missing_field(self.fields[1])?,
+        };
+        Ok((a, b))
+    }
+}
+
+/// The value of a missing field, which is `None` for an `Option`
+fn missing_field<'de, T, E>(field: &'static str) -> Result<T, E>
+where
+    T: Deserialize<'de>,
+    E: de::Error,
+{
+    let unit: UnitDeserializer<E> = ().into_deserializer();
+    T::deserialize(unit).map_err(|_| E::missing_field(field))
+}
+
+/// Serializes a variant of an enum without data
+fn serialize_unit_variant<S: Serializer>(
+    serializer: S,
+    name: &'static str,
+    variants: &'static [&'static str],
+    index: usize,
+) -> Result<S::Ok, S::Error> {
+    serializer.serialize_unit_variant(name, index as u32, variants[index])
+}
+
+/// Deserializes a variant of an enum without data, returning its index
+fn deserialize_unit_variant<'de, D: Deserializer<'de>>(
+    deserializer: D,
+    name: &'static str,
+    variants: &'static [&'static str],
+) -> Result<usize, D::Error> {
+    deserializer.deserialize_enum(name, variants, UnitVariantVisitor { name, variants })
+}
+
+struct UnitVariantVisitor {
+    name: &'static str,
+    variants: &'static [&'static str],
+}
+
+impl<'de> Visitor<'de> for UnitVariantVisitor {
+    type Value = usize;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "enum {}", self.name)
+    }
+
+    fn visit_enum<A: EnumAccess<'de>>(self, data: A) -> Result<usize, A::Error> {
+        let names = Identifier {
+            names: self.variants,
+            variants: true,
+        };
+        let (index, variant) = data.variant_seed(names)?;
+        variant.unit_variant()?;
+        // unknown variants are rejected by `Identifier`
+        Ok(index.unwrap_or_default())
+    }
+}
+
+/// Looks up a field or variant name, by name or by index
+///
+/// Unknown fields are `None` so they can be skipped, while unknown
+/// variants are an error.
+#[derive(Copy, Clone)]
+struct Identifier {
+    names: &'static [&'static str],
+    variants: bool,
+}
+
+impl<'de> de::DeserializeSeed<'de> for Identifier {
+    type Value = Option<usize>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_identifier(self)
+    }
+}
+
+impl<'de> Visitor<'de> for Identifier {
+    type Value = Option<usize>;
+
+    fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.variants {
+            f.write_str("variant identifier")
+        } else {
+            f.write_str("field identifier")
+        }
+    }
+
+    fn visit_u64<E: de::Error>(self, index: u64) -> Result<Option<usize>, E> {
+        if index < self.names.len() as u64 {
+            Ok(Some(index as usize))
+        } else if self.variants {
+            Err(E::invalid_value(Unexpected::Unsigned(index), &self))
+        } else {
+            Ok(None)
+        }
+    }
+
+    fn visit_str<E: de::Error>(self, name: &str) -> Result<Option<usize>, E> {
+        let index = self.names.iter().position(|&known| known == name);
+        if index.is_none() && self.variants {
+            return Err(E::unknown_variant(name, self.names));
+        }
+        Ok(index)
+    }
+
+    fn visit_bytes<E: de::Error>(self, name: &[u8]) -> Result<Option<usize>, E> {
+        match core::str::from_utf8(name) {
+            Ok(name) => self.visit_str(name),
+            Err(_) if self.variants => Err(E::invalid_value(Unexpected::Bytes(name), &self)),
+            Err(_) => Ok(None),
+        }
+    }
+}
+
+impl Serialize for Type {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_unit_variant(serializer, "Type", &TYPES, *self as usize)
+    }
+}
+
+impl<'de> Deserialize<'de> for Type {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let index = deserialize_unit_variant(deserializer, "Type", &TYPES)?;
+        Ok([Type::Icann, Type::Private][index])
+    }
+}
+
+impl Serialize for Kind {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_unit_variant(serializer, "Kind", &KINDS, *self as usize)
+    }
+}
+
+impl<'de> Deserialize<'de> for Kind {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let index = deserialize_unit_variant(deserializer, "Kind", &KINDS)?;
+        let kinds = [
+            Kind::Normal,
+            Kind::Wildcard,
+            Kind::Exception,
+            Kind::Implicit,
+        ];
+        Ok(kinds[index])
+    }
+}
+
+impl Serialize for Info {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_pair(serializer, "Info", &INFO_FIELDS, &self.len, &self.typ)
+    }
+}
+
+impl<'de> Deserialize<'de> for Info {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (len, typ) = deserialize_pair(deserializer, "Info", &INFO_FIELDS)?;
+        Ok(Info { len, typ })
+    }
+}
+
+impl Serialize for Match {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serialize_pair(serializer, "Match", &MATCH_FIELDS, &self.info, &self.kind)
+    }
+}
+
+impl<'de> Deserialize<'de> for Match {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let (info, kind) = deserialize_pair(deserializer, "Match", &MATCH_FIELDS)?;
+        Ok(Match { info, kind })
+    }
+}
+
+impl Serialize for Suffix<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = core::str::from_utf8(self.bytes).map_err(S::Error::custom)?;
+        serialize_pair(serializer, "Suffix", &SUFFIX_FIELDS, name, &self.typ)
+    }
+}
+
+impl Serialize for Domain<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let name = core::str::from_utf8(self.bytes).map_err(S::Error::custom)?;
+        serialize_pair(serializer, "Domain", &DOMAIN_FIELDS, name, &self.suffix)
+    }
+}
+
+#[cfg(feature = "alloc")]
+mod owned {
+    use super::{deserialize_pair, DOMAIN_FIELDS, SUFFIX_FIELDS};
+    use crate::owned::{DomainBuf, SuffixBuf};
+    use alloc::borrow::ToOwned;
+    use alloc::string::String;
+    use core::fmt;
+    use serde::de::{Deserialize, Deserializer, Error as _, Visitor};
+    use serde::ser::{Serialize, Serializer};
+
+    /// An owned name, copied from a borrowed string so serde's own `alloc`
+    /// feature isn't needed
+    struct Name(String);
+
+    impl<'de> Deserialize<'de> for Name {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_str(NameVisitor)
+        }
+    }
+
+    struct NameVisitor;
+
+    impl Visitor<'_> for NameVisitor {
+        type Value = Name;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a domain name")
+        }
+
+        fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<Name, E> {
+            Ok(Name(name.to_owned()))
+        }
+    }
+
+    impl Serialize for SuffixBuf {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_suffix().serialize(serializer)
+        }
+    }
+
+    impl Serialize for DomainBuf {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            self.as_domain().serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for SuffixBuf {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let (Name(name), typ) = deserialize_pair(deserializer, "Suffix", &SUFFIX_FIELDS)?;
+            Ok(SuffixBuf {
+                bytes: name.into_bytes(),
+                typ,
+            })
+        }
+    }
+
+    impl<'de> Deserialize<'de> for DomainBuf {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let (Name(name), suffix): (Name, SuffixBuf) =
+                deserialize_pair(deserializer, "Domain", &DOMAIN_FIELDS)?;
+            let suffix_len = suffix.bytes.len();
+            let root_len = name.len().saturating_sub(suffix_len + 1);
+            if root_len == 0
+                || !name.as_bytes().ends_with(&suffix.bytes)
+                || name.as_bytes()[root_len] != b'.'
+            {
+                return Err(D::Error::custom("domain name does not end with its suffix"));
+            }
+            Ok(DomainBuf {
+                bytes: name.into_bytes(),
+                suffix_len,
+                typ: suffix.typ,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::fixture::Tld;
+    use crate::{Info, Kind, List, Match, Type};
+
+    /// Like `Tld` but every suffix is an ICANN one, so the type is serialized
+    struct Icann;
+
+    impl List for Icann {
+        fn find<'a, T>(&self, labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            Info {
+                typ: Some(Type::Icann),
+                ..Tld.find(labels)
+            }
+        }
+    }
+
+    #[test]
+    fn serialize_domain() {
+        let domain = Icann.domain(b"www.example.com.").expect("domain name");
+        let json = serde_json::to_string(&domain).expect("json");
+        assert_eq!(
+            json,
+            r#"{"name":"example.com.","suffix":{"name":"com.","typ":"Icann"}}"#
+        );
+    }
+
+    #[test]
+    fn info_round_trip() {
+        let info = Info {
+            len: 3,
+            typ: Some(Type::Private),
+        };
+        let json = serde_json::to_string(&info).expect("json");
+        assert_eq!(json, r#"{"len":3,"typ":"Private"}"#);
+        assert_eq!(serde_json::from_str::<Info>(&json).expect("info"), info);
+    }
+
+    #[test]
+    fn match_round_trip() {
+        let found = Match {
+            info: Info { len: 5, typ: None },
+            kind: Kind::Wildcard,
+        };
+        let json = serde_json::to_string(&found).expect("json");
+        assert_eq!(json, r#"{"info":{"len":5,"typ":null},"kind":"Wildcard"}"#);
+        assert_eq!(serde_json::from_str::<Match>(&json).expect("match"), found);
+    }
+
+    #[test]
+    fn deserialize_like_derive() {
+        let info = Info { len: 3, typ: None };
+        let from_str = |json| serde_json::from_str::<Info>(json).ok();
+        assert_eq!(from_str(r#"{"len":3}"#), Some(info));
+        assert_eq!(from_str(r#"{"len":3,"other":[1,2]}"#), Some(info));
+        assert_eq!(from_str(r#"[3,null]"#), Some(info));
+        assert_eq!(from_str(r#"{"typ":null}"#), None);
+        assert_eq!(from_str(r#"{"len":3,"len":3}"#), None);
+        assert_eq!(from_str(r#"{"len":3,"typ":"Other"}"#), None);
+        assert_eq!(
+            from_str(r#"{"len":3,"typ":"Icann"}"#).and_then(|info| info.typ),
+            Some(Type::Icann)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "alloc")]
+    fn owned_round_trip() {
+        use crate::owned::{DomainBuf, SuffixBuf};
+
+        let domain = Icann.domain(b"example.com.").expect("domain name");
+        let json = serde_json::to_string(&domain).expect("json");
+        let owned: DomainBuf = serde_json::from_str(&json).expect("domain name");
+        assert_eq!(owned.as_bytes(), b"example.com.");
+        assert_eq!(owned.suffix().typ(), Some(Type::Icann));
+        assert!(owned.suffix().is_fqdn());
+
+        let json = serde_json::to_string(&domain.suffix()).expect("json");
+        let owned: SuffixBuf = serde_json::from_str(&json).expect("public suffix");
+        assert_eq!(owned.as_bytes(), b"com.");
+        assert_eq!(owned.typ(), Some(Type::Icann));
+
+        let json = r#"{"name":"example.com","suffix":{"name":"org","typ":null}}"#;
+        assert!(serde_json::from_str::<DomainBuf>(json).is_err());
+        let json = r#"{"name":"com","suffix":{"name":"com","typ":null}}"#;
+        assert!(serde_json::from_str::<DomainBuf>(json).is_err());
+    }
+}
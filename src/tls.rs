@@ -0,0 +1,146 @@
+//! Checks for wildcard names in TLS certificates
+//!
+//! The CA/Browser Forum Baseline Requirements (section 3.2.2.6) forbid
+//! wildcard certificates like `*.co.uk` whose wildcard would cover every
+//! name under a public suffix. Suffixes in the private section of the
+//! list, like `github.io`, may only be covered by the operator of that
+//! suffix, so [`Error::PublicSuffix`] reports which section matched.
+
+use crate::{strip_dot, validate_name, List, NameError, Type};
+use core::fmt;
+
+/// Why a certificate name was rejected
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
+pub enum Error {
+    /// A `*` appears anywhere other than as the whole leftmost label
+    MisplacedWildcard,
+    /// The name after the wildcard is not a valid domain name
+    InvalidName(NameError),
+    /// The wildcard would cover every name under a public suffix
+    ///
+    /// The type is `None` if no rule in the list matched, which means the
+    /// name after the wildcard is a TLD the list doesn't know.
+    PublicSuffix(Option<Type>),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::MisplacedWildcard => f.write_str("`*` is only allowed as the leftmost label"),
+            Error::InvalidName(error) => error.fmt(f),
+            Error::PublicSuffix(Some(Type::Icann)) => {
+                f.write_str("wildcard covers an ICANN public suffix")
+            }
+            Error::PublicSuffix(Some(Type::Private)) => {
+                f.write_str("wildcard covers a private public suffix")
+            }
+            Error::PublicSuffix(None) => f.write_str("wildcard covers an unknown TLD"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
+
+/// Checks a DNS name from a certificate, like `*.example.co.uk`
+///
+/// Names without a wildcard are only checked for a misplaced `*`. For
+/// wildcard names the part after `*.` must be a valid domain name that is
+/// not itself a public suffix. Names are not lowercased, so wrap
+/// case-sensitive lists in [`case::CaseInsensitive`].
+///
+/// [`case::CaseInsensitive`]: ../case/struct.CaseInsensitive.html
+pub fn check_name<L: List + ?Sized>(list: &L, name: &[u8]) -> Result<(), Error> {
+    if !name.starts_with(b"*.") {
+        return if name.contains(&b'*') {
+            Err(Error::MisplacedWildcard)
+        } else {
+            Ok(())
+        };
+    }
+    let base = &name[2..];
+    if base.contains(&b'*') {
+        return Err(Error::MisplacedWildcard);
+    }
+    validate_name(base).map_err(Error::InvalidName)?;
+    let base = strip_dot(base);
+    match list.suffix(base) {
+        Some(suffix) if suffix.trim().as_bytes().len() == base.len() => {
+            Err(Error::PublicSuffix(suffix.typ()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{check_name, Error};
+    use crate::{Info, List, NameError, Type};
+
+    /// Knows `co.uk` and the private `github.io`
+    struct Uk;
+
+    impl List for Uk {
+        fn find<'a, T>(&self, mut labels: T) -> Info
+        where
+            T: Iterator<Item = &'a [u8]>,
+        {
+            match (labels.next(), labels.next()) {
+                (Some(b"uk"), Some(b"co")) => Info {
+                    len: 5,
+                    typ: Some(Type::Icann),
+                },
+                (Some(b"io"), Some(b"github")) => Info {
+                    len: 9,
+                    typ: Some(Type::Private),
+                },
+                (Some(tld @ b"uk"), _) | (Some(tld @ b"io"), _) => Info {
+                    len: tld.len(),
+                    typ: Some(Type::Icann),
+                },
+                (Some(label), _) => Info {
+                    len: label.len(),
+                    typ: None,
+                },
+                (None, _) => Info { len: 0, typ: None },
+            }
+        }
+    }
+
+    #[test]
+    fn allowed() {
+        assert_eq!(check_name(&Uk, b"*.example.co.uk"), Ok(()));
+        assert_eq!(check_name(&Uk, b"*.example.co.uk."), Ok(()));
+        assert_eq!(check_name(&Uk, b"*.user.github.io"), Ok(()));
+        assert_eq!(check_name(&Uk, b"www.example.co.uk"), Ok(()));
+        assert_eq!(check_name(&Uk, b"co.uk"), Ok(()));
+    }
+
+    #[test]
+    fn public_suffixes() {
+        let icann = Err(Error::PublicSuffix(Some(Type::Icann)));
+        assert_eq!(check_name(&Uk, b"*.co.uk"), icann);
+        assert_eq!(check_name(&Uk, b"*.uk"), icann);
+        assert_eq!(check_name(&Uk, b"*.co.uk."), icann);
+        let private = Err(Error::PublicSuffix(Some(Type::Private)));
+        assert_eq!(check_name(&Uk, b"*.github.io"), private);
+        assert_eq!(check_name(&Uk, b"*.corp"), Err(Error::PublicSuffix(None)));
+    }
+
+    #[test]
+    fn invalid() {
+        let misplaced = Err(Error::MisplacedWildcard);
+        assert_eq!(check_name(&Uk, b"*"), misplaced);
+        assert_eq!(check_name(&Uk, b"f*.example.co.uk"), misplaced);
+        assert_eq!(check_name(&Uk, b"www.*.co.uk"), misplaced);
+        assert_eq!(check_name(&Uk, b"*.*.example.co.uk"), misplaced);
+        assert_eq!(
+            check_name(&Uk, b"*."),
+            Err(Error::InvalidName(NameError::Empty))
+        );
+        assert_eq!(
+            check_name(&Uk, b"*..co.uk"),
+            Err(Error::InvalidName(NameError::EmptyLabel))
+        );
+    }
+}
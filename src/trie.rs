@@ -0,0 +1,497 @@
+//! A reference `List` implementation backed by a label trie
+//!
+//! This is meant for small tools and for testing. It builds the list at
+//! runtime from the `public_suffix_list.dat` format instead of generating
+//! code at compile time like the `psl` crate does.
+
+use crate::dat::{self, Comment, Entry, Error, ErrorKind};
+use crate::{walk, Info, Kind, List, Match, Metadata, Type};
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::str::FromStr;
+
+/// A public suffix list stored as a trie of labels
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub struct Trie {
+    pub(crate) root: Node,
+    /// The comments above the groups of rules, shared by the rules in a group
+    comments: Vec<Box<str>>,
+}
+
+#[derive(Clone, Default, Eq, PartialEq, Debug)]
+pub(crate) struct Node {
+    pub(crate) children: BTreeMap<Box<[u8]>, Node>,
+    /// A normal or exception rule ending at this node
+    pub(crate) rule: Option<(Kind, Type)>,
+    /// A `*.` rule covering the children of this node
+    pub(crate) wildcard: Option<Type>,
+    rule_source: Option<Source>,
+    wildcard_source: Option<Source>,
+}
+
+/// Where a rule was found in the list source
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+struct Source {
+    line: usize,
+    /// The index of the comment in `Trie::comments`
+    comment: Option<usize>,
+}
+
+impl Trie {
+    /// Creates an empty list
+    #[inline]
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds a list from `src`, which must be in the `public_suffix_list.dat` format
+    ///
+    /// Invalid rules are skipped. Use [`Trie::try_parse`] to reject them instead.
+    ///
+    /// [`Trie::try_parse`]: #method.try_parse
+    #[must_use]
+    pub fn parse(src: &str) -> Self {
+        let mut trie = Self::new();
+        for entry in dat::entries(src).flatten() {
+            trie.insert(entry);
+        }
+        trie
+    }
+
+    /// Builds a list from `src`, returning the first problem found in it
+    ///
+    /// On top of the checks done by [`dat::entries`] this rejects duplicate
+    /// rules and exception rules that no wildcard rule covers.
+    ///
+    /// [`dat::entries`]: ../dat/fn.entries.html
+    pub fn try_parse(src: &str) -> Result<Self, Error> {
+        let mut trie = Self::new();
+        let mut exceptions = Vec::new();
+        for entry in dat::entries(src) {
+            let entry = entry?;
+            if !trie.insert(entry) {
+                return Err(error(entry, ErrorKind::DuplicateRule));
+            }
+            if entry.kind == Kind::Exception {
+                exceptions.push(entry);
+            }
+        }
+        for entry in exceptions {
+            let covered = entry
+                .name
+                .find('.')
+                .and_then(|pos| trie.node(&entry.name[pos + 1..]))
+                .and_then(|node| node.wildcard)
+                .is_some();
+            if !covered {
+                return Err(error(entry, ErrorKind::UncoveredException));
+            }
+        }
+        Ok(trie)
+    }
+
+    /// Adds a rule to the list
+    ///
+    /// Returns `false` if the list already had a rule with the same name
+    /// and kind, or a normal rule when adding an exception rule for the
+    /// same name or the other way round. The old rule is replaced.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `entry` is an implicit rule, which isn't in any list.
+    pub fn insert(&mut self, entry: Entry<'_>) -> bool {
+        assert!(
+            entry.kind != Kind::Implicit,
+            "implicit rules can't be inserted"
+        );
+        let source = Source {
+            line: entry.line,
+            comment: entry.comment.map(|comment| self.comment(comment)),
+        };
+        let mut node = &mut self.root;
+        for label in entry.name.rsplit('.') {
+            node = node.children.entry(label.as_bytes().into()).or_default();
+        }
+        match entry.kind {
+            Kind::Wildcard => {
+                node.wildcard_source = Some(source);
+                node.wildcard.replace(entry.typ).is_none()
+            }
+            Kind::Normal | Kind::Exception | Kind::Implicit => {
+                node.rule_source = Some(source);
+                node.rule.replace((entry.kind, entry.typ)).is_none()
+            }
+        }
+    }
+
+    /// The index of `comment` in `comments`, adding it if it's new
+    ///
+    /// Rules are usually inserted group by group so only the last comment
+    /// is checked.
+    fn comment(&mut self, comment: Comment<'_>) -> usize {
+        if self.comments.last().map(|last| &**last) != Some(comment.as_str()) {
+            self.comments.push(comment.as_str().into());
+        }
+        self.comments.len() - 1
+    }
+
+    fn node(&self, name: &str) -> Option<&Node> {
+        let mut node = &self.root;
+        for label in name.rsplit('.') {
+            node = node.children.get(label.as_bytes())?;
+        }
+        Some(node)
+    }
+}
+
+impl FromStr for Trie {
+    type Err = Error;
+
+    #[inline]
+    fn from_str(src: &str) -> Result<Self, Error> {
+        Self::try_parse(src)
+    }
+}
+
+fn error(entry: Entry<'_>, kind: ErrorKind) -> Error {
+    Error {
+        line: entry.line,
+        column: entry.column,
+        kind,
+    }
+}
+
+impl List for Trie {
+    #[inline]
+    fn find<'a, T>(&self, labels: T) -> Info
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        self.find_match(labels).info
+    }
+
+    #[inline]
+    fn find_match<'a, T>(&self, labels: T) -> Match
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        walk::find_match(&self.root, labels)
+    }
+
+    #[inline]
+    fn find_all<'a, T>(&self, labels: T, out: &mut [Info]) -> usize
+    where
+        T: Iterator<Item = &'a [u8]> + Clone,
+    {
+        walk::find_all(&self.root, labels, out)
+    }
+
+    /// Reuses the walk of the previous name for the labels they share
+    #[inline]
+    fn find_batch(&self, names: &[&[u8]], out: &mut [Info]) {
+        walk::find_batch(&self.root, names, out);
+    }
+
+    fn find_metadata<'a, T>(&self, labels: T) -> Option<Metadata<'_>>
+    where
+        T: Iterator<Item = &'a [u8]>,
+    {
+        let labels: Vec<_> = labels.collect();
+        let Match { info, kind } = self.find_match(labels.iter().copied());
+        let mut count = 0;
+        let mut len = 0;
+        while len < info.len && count < labels.len() {
+            len += labels[count].len() + usize::from(count > 0);
+            count += 1;
+        }
+        let (count, wildcard) = match kind {
+            Kind::Normal => (count, false),
+            Kind::Wildcard => (count - 1, true),
+            Kind::Exception => (count + 1, false),
+            Kind::Implicit => return None,
+        };
+        let typ = info.typ?;
+        let mut node = &self.root;
+        for label in &labels[..count] {
+            node = node.children.get(*label)?;
+        }
+        let source = if wildcard {
+            node.wildcard_source
+        } else {
+            node.rule_source
+        }?;
+        Some(Metadata {
+            comment: source.comment.map(|index| Comment {
+                src: &self.comments[index],
+                typ,
+            }),
+            line: Some(source.line),
+        })
+    }
+}
+
+impl walk::Node for &Node {
+    #[inline]
+    fn child(self, label: &[u8]) -> Option<Self> {
+        self.children.get(label)
+    }
+
+    #[inline]
+    fn rule(self) -> Option<(Kind, Type)> {
+        self.rule
+    }
+
+    #[inline]
+    fn wildcard(self) -> Option<Type> {
+        self.wildcard
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod test {
+    use super::Trie;
+    use crate::dat::{Entry, Error, ErrorKind};
+    use crate::{Kind, List, Type};
+
+    /// The rules needed by the upstream conformance tests
+    pub(crate) const SRC: &str = "\
+// ===BEGIN ICANN DOMAINS===
+ac
+biz
+com
+jp
+ac.jp
+kyoto.jp
+ide.kyoto.jp
+*.kobe.jp
+!city.kobe.jp
+*.ck
+!www.ck
+*.mm
+us
+ak.us
+k12.ak.us
+cn
+com.cn
+公司.cn
+xn--55qx5d.cn
+中国
+xn--fiqs8s
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+uk.com
+blogspot.com
+// ===END PRIVATE DOMAINS===
+";
+
+    #[test]
+    fn sections() {
+        let list = Trie::parse(SRC);
+        let suffix = list.suffix(b"example.co.jp").expect("public suffix");
+        assert_eq!(suffix, "jp");
+        assert_eq!(suffix.typ(), Some(Type::Icann));
+        let suffix = list.suffix(b"foo.blogspot.com").expect("public suffix");
+        assert_eq!(suffix, "blogspot.com");
+        assert_eq!(suffix.typ(), Some(Type::Private));
+        let suffix = list.suffix(b"example.test").expect("public suffix");
+        assert_eq!(suffix, "test");
+        assert_eq!(suffix.typ(), None);
+    }
+
+    #[test]
+    fn rule_kinds() {
+        let list = Trie::parse(SRC);
+        let rule = list.rule(b"a.b.c.kobe.jp").expect("rule");
+        assert_eq!(rule.kind(), Kind::Wildcard);
+        assert_eq!(rule.suffix(), "c.kobe.jp");
+        let rule = list.rule(b"www.city.kobe.jp").expect("rule");
+        assert_eq!(rule.kind(), Kind::Exception);
+        assert_eq!(rule.suffix(), "kobe.jp");
+        assert_eq!(rule.as_bytes(), b"city.kobe.jp");
+        let rule = list.rule(b"example.ac.jp").expect("rule");
+        assert_eq!(rule.kind(), Kind::Normal);
+        assert_eq!(rule.suffix(), "ac.jp");
+        let rule = list.rule(b"example.jp.test").expect("rule");
+        assert_eq!(rule.kind(), Kind::Implicit);
+        assert_eq!(rule.suffix(), "test");
+    }
+
+    #[test]
+    fn try_parse() {
+        assert_eq!(SRC.parse(), Ok(Trie::parse(SRC)));
+
+        let src = "// ===BEGIN ICANN DOMAINS===\nck\n*.ck\nck\n";
+        let error = Error {
+            line: 4,
+            column: 1,
+            kind: ErrorKind::DuplicateRule,
+        };
+        assert_eq!(Trie::try_parse(src), Err(error));
+
+        let src = "// ===BEGIN ICANN DOMAINS===\n!www.ck\nck\n";
+        let error = Error {
+            line: 2,
+            column: 1,
+            kind: ErrorKind::UncoveredException,
+        };
+        assert_eq!(Trie::try_parse(src), Err(error));
+
+        let src = "// ===BEGIN ICANN DOMAINS===\n!www.ck\n*.ck\n";
+        assert!(Trie::try_parse(src).is_ok());
+
+        let src = "// ===BEGIN ICANN DOMAINS===\n*.ck\nwww.ck\n!www.ck\n";
+        let error = Error {
+            line: 4,
+            column: 1,
+            kind: ErrorKind::DuplicateRule,
+        };
+        assert_eq!(Trie::try_parse(src), Err(error));
+    }
+
+    #[test]
+    #[should_panic(expected = "implicit rules can't be inserted")]
+    fn insert_implicit() {
+        let entry = Entry {
+            name: "example.com",
+            kind: Kind::Implicit,
+            typ: Type::Icann,
+            line: 1,
+            column: 1,
+            comment: None,
+        };
+        Trie::new().insert(entry);
+    }
+
+    #[test]
+    fn all_suffixes() {
+        /// Only has `find` so it uses the default `find_all`
+        struct FindOnly(Trie);
+
+        impl List for FindOnly {
+            fn find<'a, T>(&self, labels: T) -> crate::Info
+            where
+                T: Iterator<Item = &'a [u8]>,
+            {
+                self.0.find(labels)
+            }
+        }
+
+        let trie = Trie::parse(SRC);
+        let find_only = FindOnly(trie.clone());
+        for name in [
+            "a.b.c.example.com",
+            "www.city.kobe.jp",
+            "a.b.c.kobe.jp",
+            "foo.bar.ck",
+            "www.ck",
+            "食狮.公司.cn",
+            "example.local",
+        ]
+        .iter()
+        {
+            let name = name.as_bytes();
+            assert!(trie.all_suffixes(name).eq(find_only.all_suffixes(name)));
+        }
+
+        let suffixes = trie.all_suffixes(b"a.b.c.kobe.jp");
+        assert!(suffixes.eq(["jp", "c.kobe.jp"].iter().copied()));
+    }
+
+    #[test]
+    fn find_batch() {
+        let trie = Trie::parse(SRC);
+        check_batch(&trie);
+    }
+
+    /// Checks that `find_batch` agrees with `find` whatever order the
+    /// names come in, including names that share some of their labels
+    pub(crate) fn check_batch<L: List>(list: &L) {
+        extern crate alloc;
+        use crate::{canonical, Info};
+        use alloc::vec;
+
+        let long = "a.".repeat(200) + "example.com";
+        let mut names = vec![
+            &b"www.example.com"[..],
+            b"example.com.",
+            b"a.example.com",
+            b"b.example.com",
+            b"com",
+            b"a.b.c.kobe.jp",
+            b"www.city.kobe.jp",
+            b"city.kobe.jp",
+            b"b.c.kobe.jp",
+            b"www.ck",
+            b"foo.www.ck",
+            b"a.www.ck",
+            b"bar.ck",
+            "食狮.公司.cn".as_bytes(),
+            "公司.cn".as_bytes(),
+            b"printer.corp",
+            b"x.printer.corp",
+            long.as_bytes(),
+            b"",
+            b".",
+            b"com..",
+            b"..com",
+        ];
+        let check = |names: &[&[u8]]| {
+            let mut out = vec![Info { len: 0, typ: None }; names.len()];
+            list.find_batch(names, &mut out);
+            for (name, info) in names.iter().zip(&out) {
+                let expected = list.find(crate::reversed_labels(name).0);
+                assert_eq!(*info, expected, "{:?}", core::str::from_utf8(name));
+            }
+        };
+        check(&names);
+        names.sort_by(|a, b| canonical::cmp(a, b));
+        check(&names);
+        names.reverse();
+        check(&names);
+    }
+
+    #[test]
+    fn metadata() {
+        let trie = Trie::parse(
+            "\
+// ===BEGIN ICANN DOMAINS===
+// ck : https://en.wikipedia.org/wiki/.ck
+*.ck
+!www.ck
+// ===END ICANN DOMAINS===
+// ===BEGIN PRIVATE DOMAINS===
+// Google, Inc. : https://www.google.com
+// Submitted by Eduardo Vela <evn@google.com>
+blogspot.com
+",
+        );
+
+        let metadata = trie.metadata(b"foo.blogspot.com").expect("metadata");
+        assert_eq!(metadata.organisation(), Some("Google, Inc."));
+        assert_eq!(metadata.line, Some(9));
+        let comment = metadata.comment.expect("comment");
+        assert_eq!(comment.submitter(), Some("Eduardo Vela <evn@google.com>"));
+
+        let metadata = trie.metadata(b"foo.bar.ck").expect("metadata");
+        assert_eq!(metadata.line, Some(3));
+        let metadata = trie.metadata(b"www.ck").expect("metadata");
+        assert_eq!(metadata.line, Some(4));
+        assert_eq!(metadata.organisation(), None);
+        let comment = metadata.comment.expect("comment");
+        assert_eq!(
+            comment.as_str(),
+            "// ck : https://en.wikipedia.org/wiki/.ck"
+        );
+
+        assert_eq!(trie.metadata(b"example.com"), None);
+    }
+
+    #[test]
+    #[cfg(feature = "conformance")]
+    fn conformance() {
+        let report = crate::conformance::run(&Trie::parse(SRC));
+        assert!(report.is_ok(), "{}", report);
+    }
+}
@@ -0,0 +1,264 @@
+//! The lookup algorithm shared by the trie based lists
+
+use crate::{reversed_labels, Info, Kind, Match, Type};
+
+/// How many labels of the previous name `find_batch` remembers
+const SHARED_LABELS: usize = 8;
+
+/// A node in a trie of labels
+pub(crate) trait Node: Copy {
+    /// The child node for `label`
+    fn child(self, label: &[u8]) -> Option<Self>;
+
+    /// A normal or exception rule ending at this node
+    fn rule(self) -> Option<(Kind, Type)>;
+
+    /// A `*.` rule covering the children of this node
+    fn wildcard(self) -> Option<Type>;
+}
+
+/// Walks the trie from `root` following `labels`, which must be in reverse order
+pub(crate) fn find_match<'a, N, T>(root: N, labels: T) -> Match
+where
+    N: Node,
+    T: Iterator<Item = &'a [u8]>,
+{
+    let mut node = root;
+    let mut len = 0;
+    let mut found = None;
+    let mut implicit = 0;
+    for label in labels {
+        let label_len = if len == 0 {
+            implicit = label.len();
+            label.len()
+        } else {
+            len + 1 + label.len()
+        };
+        let (child, matched) = descend(node, label, len, label_len, found);
+        found = matched;
+        node = match child {
+            Some(child) => child,
+            None => break,
+        };
+        len = label_len;
+    }
+    found.unwrap_or(Match {
+        info: Info {
+            len: implicit,
+            typ: None,
+        },
+        kind: Kind::Implicit,
+    })
+}
+
+/// Follows `label` from `parent`, updating the match found so far
+///
+/// `len` is the length of the name before `label` and `label_len` the
+/// length including it. The child is `None` if there is no such node or
+/// if an exception rule ended the walk.
+#[inline]
+fn descend<N: Node>(
+    parent: N,
+    label: &[u8],
+    len: usize,
+    label_len: usize,
+    mut found: Option<Match>,
+) -> (Option<N>, Option<Match>) {
+    let child = parent.child(label);
+    let rule = child.and_then(Node::rule);
+    if let Some((Kind::Exception, typ)) = rule {
+        let info = Info {
+            len,
+            typ: Some(typ),
+        };
+        return (
+            None,
+            Some(Match {
+                info,
+                kind: Kind::Exception,
+            }),
+        );
+    }
+    if let Some(typ) = parent.wildcard() {
+        found = Some(Match {
+            info: Info {
+                len: label_len,
+                typ: Some(typ),
+            },
+            kind: Kind::Wildcard,
+        });
+    }
+    if let Some((Kind::Normal, typ)) = rule {
+        found = Some(Match {
+            info: Info {
+                len: label_len,
+                typ: Some(typ),
+            },
+            kind: Kind::Normal,
+        });
+    }
+    (child, found)
+}
+
+/// Walks the trie from `root` following `labels`, recording every known
+/// suffix on the way from the shortest to the longest
+///
+/// Returns how many suffixes were written to `out`.
+pub(crate) fn find_all<'a, N, T>(root: N, labels: T, out: &mut [Info]) -> usize
+where
+    N: Node,
+    T: Iterator<Item = &'a [u8]>,
+{
+    let mut node = root;
+    let mut len = 0;
+    let mut count = 0;
+    for (index, label) in labels.enumerate() {
+        if count == out.len() {
+            break;
+        }
+        len += usize::from(index > 0) + label.len();
+        let child = node.child(label);
+        let typ = match child.and_then(Node::rule) {
+            Some((Kind::Exception, _)) => break,
+            Some((_, typ)) => Some(typ),
+            None => node.wildcard(),
+        };
+        if let Some(typ) = typ {
+            out[count] = Info {
+                len,
+                typ: Some(typ),
+            };
+            count += 1;
+        }
+        node = match child {
+            Some(child) => child,
+            None => break,
+        };
+    }
+    count
+}
+
+/// Walks the trie once for each of `names`, reusing the part of the walk
+/// shared with the previous name
+///
+/// Names that end in the same labels share the nodes visited for those
+/// labels, so sorting names in canonical order before a batch lookup means
+/// most of them only need a fresh walk from their registrable domain down.
+pub(crate) fn find_batch<N: Node>(root: N, names: &[&[u8]], out: &mut [Info]) {
+    assert_eq!(names.len(), out.len(), "one output slot per name");
+    // the last labels of the previous name with the node and match after each
+    let mut path: [(&[u8], Option<N>, Option<Match>); SHARED_LABELS] =
+        [(&[], None, None); SHARED_LABELS];
+    let mut path_len = 0;
+    for (name, info) in names.iter().zip(out) {
+        let shared_len = path_len;
+        let mut shared = true;
+        let mut node = Some(root);
+        let mut len = 0;
+        let mut found = None;
+        let mut implicit = 0;
+        let mut depth = 0;
+        for label in reversed_labels(name).0 {
+            let parent = match node {
+                Some(parent) => parent,
+                None => break,
+            };
+            let label_len = if len == 0 {
+                implicit = label.len();
+                label.len()
+            } else {
+                len + 1 + label.len()
+            };
+            shared = shared && depth < shared_len && path[depth].0 == label;
+            if shared {
+                node = path[depth].1;
+                found = path[depth].2;
+            } else {
+                let (child, matched) = descend(parent, label, len, label_len, found);
+                node = child;
+                found = matched;
+                if let Some(entry) = path.get_mut(depth) {
+                    *entry = (label, node, found);
+                }
+            }
+            len = label_len;
+            depth += 1;
+        }
+        path_len = depth.min(SHARED_LABELS);
+        *info = match found {
+            Some(found) => found.info,
+            None => Info {
+                len: implicit,
+                typ: None,
+            },
+        };
+    }
+}
+
+/// Continues a walk by one label
+///
+/// `parent` is the node reached by the labels before `label`, or `None`
+/// if they fell off the trie, and `prev` is the match for those labels,
+/// or `None` if `label` is the first one. `len` is the length of the name
+/// including `label`.
+pub(crate) fn step<N: Node>(
+    parent: Option<N>,
+    label: &[u8],
+    len: usize,
+    prev: Option<Match>,
+) -> (Option<N>, Match) {
+    let mut found = match prev {
+        Some(prev) if prev.kind == Kind::Exception => return (None, prev),
+        Some(prev) => prev,
+        None => Match {
+            info: Info { len, typ: None },
+            kind: Kind::Implicit,
+        },
+    };
+    let parent = match parent {
+        Some(parent) => parent,
+        None => return (None, found),
+    };
+    let child = parent.child(label);
+    if let Some((Kind::Exception, typ)) = child.and_then(Node::rule) {
+        let prev_len = if prev.is_some() {
+            len - label.len() - 1
+        } else {
+            0
+        };
+        let info = Info {
+            len: prev_len,
+            typ: Some(typ),
+        };
+        return (
+            None,
+            Match {
+                info,
+                kind: Kind::Exception,
+            },
+        );
+    }
+    if let Some(typ) = parent.wildcard() {
+        found = Match {
+            info: Info {
+                len,
+                typ: Some(typ),
+            },
+            kind: Kind::Wildcard,
+        };
+    }
+    let child = match child {
+        Some(child) => child,
+        None => return (None, found),
+    };
+    if let Some((Kind::Normal, typ)) = child.rule() {
+        found = Match {
+            info: Info {
+                len,
+                typ: Some(typ),
+            },
+            kind: Kind::Normal,
+        };
+    }
+    (Some(child), found)
+}